@@ -73,8 +73,7 @@ fn main() {
                     // set parameters and call `putchar`
                     code.emit_mov(Dx, word_ptr(Rcx + Rax * 2)).unwrap();
                     code.emit_mov(Rcx, qword_ptr(Rbp - 24)).unwrap();
-                    code.emit_mov(Rax, putchar as u64).unwrap();
-                    code.emit_call(Rax).unwrap();
+                    code.emit_call_abs(putchar as u64, Rax).unwrap();
 
                     // restore `rax`, `rcx` and `rdx` from stack
                     code.emit_mov(Rax, qword_ptr(Rbp - 8)).unwrap();
@@ -89,8 +88,7 @@ fn main() {
 
                     // set parameters and call `getchar`
                     code.emit_mov(Rcx, Rdx).unwrap();
-                    code.emit_mov(Rax, getchar as u64).unwrap();
-                    code.emit_call(Rax).unwrap();
+                    code.emit_call_abs(getchar as u64, Rax).unwrap();
                     code.emit_movzx(Dx, Al).unwrap();
 
                     // restore `rax` and `rcx` from stack
@@ -124,8 +122,7 @@ fn main() {
         }
 
         // function epilogue
-        code.emit_add(Rsp, 32u8).unwrap();
-        code.emit_pop(Rbp).unwrap();
+        code.emit_leave().unwrap();
         code.emit_ret().unwrap();
     }
 
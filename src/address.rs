@@ -0,0 +1,76 @@
+use std::io::Cursor;
+
+use mitte_core::EmitSlice;
+
+
+/// Emitters that know the final address their code will run at.
+pub trait Address {
+    /// Returns the address of the next emitted byte, if it is already known.
+    fn address(&self) -> Option<u64>;
+}
+
+/// A `Vec` may move its code when it grows, so its address is never known.
+impl Address for Vec<u8> {
+    #[inline]
+    fn address(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// The buffer behind a cursor may be copied elsewhere before it runs, so its
+/// address is never known. Use `Based` to run code at a known address.
+impl Address for Cursor<&mut [u8]> {
+    #[inline]
+    fn address(&self) -> Option<u64> {
+        None
+    }
+}
+
+
+/// Wraps an emitter whose code will run at a known address.
+pub struct Based<E> {
+    base: u64,
+    len: u64,
+    inner: E,
+}
+
+impl<E> Based<E> {
+    /// Creates an emitter whose first emitted byte will run at `base`.
+    pub fn new(base: u64, inner: E) -> Based<E> {
+        Based {
+            base,
+            len: 0,
+            inner,
+        }
+    }
+
+    pub fn get_ref(&self) -> &E {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+}
+
+impl<E> EmitSlice for Based<E> where E: EmitSlice {
+    type Error = E::Error;
+
+    #[inline]
+    fn emit_slice(&mut self, slice: &[u8]) -> Result<(), E::Error> {
+        self.inner.emit_slice(slice)?;
+        self.len += slice.len() as u64;
+        Ok(())
+    }
+}
+
+impl<E> Address for Based<E> {
+    #[inline]
+    fn address(&self) -> Option<u64> {
+        Some(self.base.wrapping_add(self.len))
+    }
+}
@@ -1,13 +1,19 @@
+use std::convert::TryFrom;
+
 use mitte_core::{EmitSlice, Emit, Label};
 
-use reg::{Reg8, Reg16, Reg32, Reg64};
-use ptr::{Mem, Byte, Word, DWord, QWord};
+use reg::{Reg8, Reg16, Reg32, Reg64, Xmm, Ymm, Zmm, K, Masked};
+use ptr::{Mem, Vsib, Byte, Word, DWord, QWord, XmmWord, YmmWord, ZmmWord, Broadcast, Lock};
 use operand::Operand;
+use common::Register;
 use error::Error;
 use fixup::FixupKind;
+use address::Address;
+use relax::{self, Relax};
+use self::cond::Cond;
 use encode::Encode;
-use encode::{None, D, I, M, O, M1, MI, MC, MR, RM, OI, XchgSrc, XchgDst};
-use encode::{Prefix, RexW, Op, OpPlusReg, ModRm, ModRmIndex, Imm8, Imm16, Imm32, Imm64};
+use encode::{None, D, I, M, O, M1, MI, MC, MR, MRI, MRC, RM, RMI, RVM, RMV, OI, XchgSrc, XchgDst};
+use encode::{Prefix, RexW, Vex, Evex, Op, OpPlusReg, ModRm, ModRmIndex, Imm8, Imm16, Imm32, Imm64};
 
 
 struct ArithOpcodes {
@@ -132,6 +138,22 @@ struct ShiftOpcodes {
     index: u8,
 }
 
+const ROL: ShiftOpcodes = ShiftOpcodes {
+    index: 0,
+};
+
+const ROR: ShiftOpcodes = ShiftOpcodes {
+    index: 1,
+};
+
+const RCL: ShiftOpcodes = ShiftOpcodes {
+    index: 2,
+};
+
+const RCR: ShiftOpcodes = ShiftOpcodes {
+    index: 3,
+};
+
 const SHL: ShiftOpcodes = ShiftOpcodes {
     index: 4,
 };
@@ -145,8 +167,12 @@ const SAR: ShiftOpcodes = ShiftOpcodes {
 };
 
 
-mod cond {
-    pub struct Cond(pub u8);
+pub mod cond {
+    use std::fmt;
+
+    /// A condition code, as encoded in Jcc, SETcc and CMOVcc.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Cond(u8);
 
     pub const A: Cond = Cond(0x7);
     pub const AE: Cond = Cond(0x3);
@@ -178,9 +204,96 @@ mod cond {
     pub const PO: Cond = Cond(0xb);
     pub const S: Cond = Cond(0x8);
     pub const Z: Cond = Cond(0x4);
+
+    impl Cond {
+        #[inline]
+        pub fn code(self) -> u8 {
+            self.0
+        }
+
+        /// Returns the condition that holds exactly when `self` doesn't.
+        #[inline]
+        pub fn negate(self) -> Cond {
+            Cond(self.0 ^ 1)
+        }
+
+        /// Returns the condition that holds for `cmp b, a` whenever `self`
+        /// holds for `cmp a, b`, or `None` for the flag tests that have none.
+        pub fn swap_operands(self) -> Option<Cond> {
+            match self {
+                A => Some(B),
+                AE => Some(BE),
+                B => Some(A),
+                BE => Some(AE),
+                E | NE => Some(self),
+                G => Some(L),
+                GE => Some(LE),
+                L => Some(G),
+                LE => Some(GE),
+                _ => None,
+            }
+        }
+
+        /// Returns whether `self` compares its operands as signed integers.
+        #[inline]
+        pub fn is_signed(self) -> bool {
+            self.0 >= 0xc
+        }
+
+        /// Returns whether `self` compares its operands as unsigned integers.
+        #[inline]
+        pub fn is_unsigned(self) -> bool {
+            matches!(self, A | AE | B | BE)
+        }
+    }
+
+    impl fmt::Display for Cond {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            const NAMES: [&str; 16] = [
+                "o", "no", "b", "ae", "e", "ne", "be", "a",
+                "s", "ns", "p", "np", "l", "ge", "le", "g",
+            ];
+            fmt.write_str(NAMES[self.0 as usize])
+        }
+    }
 }
 
 
+
+pub mod string {
+    //! Operand sizes and repeat prefixes of the string instructions.
+
+    /// Byte elements, as in `movsb`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct B;
+
+    /// Word elements, as in `movsw`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct W;
+
+    /// Doubleword elements, as in `movsd`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct D;
+
+    /// Quadword elements, as in `movsq`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Q;
+
+    /// Repeats the instruction `rcx` times, `rep`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Rep<S>(pub S);
+
+    /// Repeats the comparison up to `rcx` times while the elements are
+    /// equal, `repe`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Repe<S>(pub S);
+
+    /// Repeats the comparison up to `rcx` times while the elements are not
+    /// equal, `repne`.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct Repne<S>(pub S);
+}
+
 macro_rules! binary_arith_op {
     ($(($Op:ident, $op:ident)),*) => {
         $(
@@ -277,6 +390,23 @@ macro_rules! binary_arith_op {
             <P: Mem> dst: DWord<P>, src: Reg32 => (MR)               Op($op.reg32), ModRm;
             <P: Mem> dst: QWord<P>, src: Reg64 => (MR) RexW,         Op($op.reg32), ModRm;
         }}
+
+        rip_label_op! { $Op {
+            dst: Reg8,  [src: Byte];
+            dst: Reg16, [src: Word];
+            dst: Reg32, [src: DWord];
+            dst: Reg64, [src: QWord];
+
+            [dst: Byte],  src: u8  => 1;
+            [dst: Word],  src: u16 => 2;
+            [dst: DWord], src: u32 => 4;
+            [dst: QWord], src: u32 => 4;
+
+            [dst: Byte],  src: Reg8  => 0;
+            [dst: Word],  src: Reg16 => 0;
+            [dst: DWord], src: Reg32 => 0;
+            [dst: QWord], src: Reg64 => 0;
+        }}
         )*
     };
 }
@@ -287,6 +417,61 @@ binary_arith_op! {
 }
 
 
+macro_rules! lock_arith_op {
+    ($(($Op:ident, $op:ident)),*) => {
+        $(
+        impl<W> $Op<Lock<Operand>, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Lock<Operand>, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst.0, src) {
+                    (BytePointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (WordPointer(d), Imm16(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Imm32(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Imm32(s)) => $Op::emit(self, Lock(d), s),
+                    (WordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (BytePointer(d), Reg8(s)) => $Op::emit(self, Lock(d), s),
+                    (WordPointer(d), Reg16(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Reg32(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Reg64(s)) => $Op::emit(self, Lock(d), s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            <P: Mem> dst: Lock<Byte<P>>, src: u8 =>
+                (MI) Op($op.imm8), ModRmIndex($op.index), Imm8;
+            <P: Mem> dst: Lock<Word<P>>, src: u16 =>
+                (MI) Prefix(0x66), Op($op.imm32), ModRmIndex($op.index), Imm16;
+            <P: Mem> dst: Lock<DWord<P>>, src: u32 =>
+                (MI) Op($op.imm32), ModRmIndex($op.index), Imm32;
+            <P: Mem> dst: Lock<QWord<P>>, src: u32 =>
+                (MI) RexW, Op($op.imm32), ModRmIndex($op.index), Imm32;
+
+            <P: Mem> dst: Lock<Word<P>>, src: u8 =>
+                (MI) Prefix(0x66), Op($op.sext_imm8), ModRmIndex($op.index), Imm8;
+            <P: Mem> dst: Lock<DWord<P>>, src: u8 =>
+                (MI)               Op($op.sext_imm8), ModRmIndex($op.index), Imm8;
+            <P: Mem> dst: Lock<QWord<P>>, src: u8 =>
+                (MI) RexW,         Op($op.sext_imm8), ModRmIndex($op.index), Imm8;
+
+            <P: Mem> dst: Lock<Byte<P>>,  src: Reg8  => (MR)               Op($op.reg8),  ModRm;
+            <P: Mem> dst: Lock<Word<P>>,  src: Reg16 => (MR) Prefix(0x66), Op($op.reg32), ModRm;
+            <P: Mem> dst: Lock<DWord<P>>, src: Reg32 => (MR)               Op($op.reg32), ModRm;
+            <P: Mem> dst: Lock<QWord<P>>, src: Reg64 => (MR) RexW,         Op($op.reg32), ModRm;
+        }}
+        )*
+    };
+}
+
+lock_arith_op! {
+    (Add, ADD), (Or, OR), (Adc, ADC), (Sbb, SBB),
+    (And, AND), (Sub, SUB), (Xor, XOR)
+}
+
+
 macro_rules! shift_op {
     ($(($Op:ident, $op:ident)),*) => {
         $(
@@ -399,9 +584,71 @@ macro_rules! shift_op {
 }
 
 shift_op! {
-    (Shl, SHL), (Shr, SHR), (Sar, SAR)
+    (Rol, ROL), (Ror, ROR), (Rcl, RCL), (Rcr, RCR),
+    (Shl, SHL), (Sal, SHL), (Shr, SHR), (Sar, SAR)
+}
+
+
+macro_rules! double_shift_op {
+    ($( ($Op:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S, C>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S, shift: C) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand, shift: Operand)
+                -> Result<(), Error<Self::Error>>
+            {
+                use operand::Operand::*;
+                match (dst, src, shift) {
+                    (Reg16(d), Reg16(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (Reg32(d), Reg32(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (Reg64(d), Reg64(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (Reg16(d), Reg16(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    (Reg32(d), Reg32(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    (Reg64(d), Reg64(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    (WordPointer(d), Reg16(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (DWordPointer(d), Reg32(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (QWordPointer(d), Reg64(s), Imm8(c)) => $Op::emit(self, d, s, c),
+                    (WordPointer(d), Reg16(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    (DWordPointer(d), Reg32(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    (QWordPointer(d), Reg64(s), Reg8(c)) => $Op::emit(self, d, s, c),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Reg16, src: Reg16, shift: u8 => (MRI) Prefix(0x66), Op(0x0f), Op($op), ModRm, Imm8;
+            dst: Reg32, src: Reg32, shift: u8 => (MRI)               Op(0x0f), Op($op), ModRm, Imm8;
+            dst: Reg64, src: Reg64, shift: u8 => (MRI) RexW,         Op(0x0f), Op($op), ModRm, Imm8;
+
+            dst: Reg16, src: Reg16, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) Prefix(0x66), Op(0x0f), Op($op + 1), ModRm;
+            dst: Reg32, src: Reg32, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) Op(0x0f), Op($op + 1), ModRm;
+            dst: Reg64, src: Reg64, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) RexW, Op(0x0f), Op($op + 1), ModRm;
+
+            <P: Mem> dst: Word<P>,  src: Reg16, shift: u8 => (MRI) Prefix(0x66), Op(0x0f), Op($op), ModRm, Imm8;
+            <P: Mem> dst: DWord<P>, src: Reg32, shift: u8 => (MRI)               Op(0x0f), Op($op), ModRm, Imm8;
+            <P: Mem> dst: QWord<P>, src: Reg64, shift: u8 => (MRI) RexW,         Op(0x0f), Op($op), ModRm, Imm8;
+
+            <P: Mem> dst: Word<P>,  src: Reg16, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) Prefix(0x66), Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: DWord<P>, src: Reg32, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: QWord<P>, src: Reg64, shift: Reg8; assert_eq!(shift, Reg8::Cl)
+                => (MRC) RexW, Op(0x0f), Op($op + 1), ModRm;
+        }}
+        )*
+    };
 }
 
+double_shift_op! {
+    (Shld, 0xa4), (Shrd, 0xac)
+}
 
 macro_rules! unary_arith_op {
     ($( ($Op:ident, $index:expr) ),*) => {
@@ -447,6 +694,77 @@ unary_arith_op! {
 }
 
 
+pub trait Imul2<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Imul2<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Reg16(d), Reg16(s)) => Imul2::emit(self, d, s),
+            (Reg32(d), Reg32(s)) => Imul2::emit(self, d, s),
+            (Reg64(d), Reg64(s)) => Imul2::emit(self, d, s),
+            (Reg16(d), WordPointer(s)) => Imul2::emit(self, d, s),
+            (Reg32(d), DWordPointer(s)) => Imul2::emit(self, d, s),
+            (Reg64(d), QWordPointer(s)) => Imul2::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Imul2 {
+    dst: Reg16, src: Reg16 => (RM) Prefix(0x66), Op(0x0f), Op(0xaf), ModRm;
+    dst: Reg32, src: Reg32 => (RM)               Op(0x0f), Op(0xaf), ModRm;
+    dst: Reg64, src: Reg64 => (RM) RexW,         Op(0x0f), Op(0xaf), ModRm;
+
+    <P: Mem> dst: Reg16, src: Word<P>  => (RM) Prefix(0x66), Op(0x0f), Op(0xaf), ModRm;
+    <P: Mem> dst: Reg32, src: DWord<P> => (RM)               Op(0x0f), Op(0xaf), ModRm;
+    <P: Mem> dst: Reg64, src: QWord<P> => (RM) RexW,         Op(0x0f), Op(0xaf), ModRm;
+}}
+
+
+pub trait Imul3<D, S, I>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S, imm: I) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Imul3<Operand, Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand, imm: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src, imm) {
+            (Reg16(d), Reg16(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg32(d), Reg32(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg64(d), Reg64(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg16(d), Reg16(s), Imm16(i)) => Imul3::emit(self, d, s, i),
+            (Reg32(d), Reg32(s), Imm32(i)) => Imul3::emit(self, d, s, i),
+            (Reg64(d), Reg64(s), Imm32(i)) => Imul3::emit(self, d, s, i),
+            (Reg16(d), WordPointer(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg32(d), DWordPointer(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg64(d), QWordPointer(s), Imm8(i)) => Imul3::emit(self, d, s, i),
+            (Reg16(d), WordPointer(s), Imm16(i)) => Imul3::emit(self, d, s, i),
+            (Reg32(d), DWordPointer(s), Imm32(i)) => Imul3::emit(self, d, s, i),
+            (Reg64(d), QWordPointer(s), Imm32(i)) => Imul3::emit(self, d, s, i),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Imul3 {
+    dst: Reg16, src: Reg16, imm: u8  => (RMI) Prefix(0x66), Op(0x6b), ModRm, Imm8;
+    dst: Reg32, src: Reg32, imm: u8  => (RMI)               Op(0x6b), ModRm, Imm8;
+    dst: Reg64, src: Reg64, imm: u8  => (RMI) RexW,         Op(0x6b), ModRm, Imm8;
+    dst: Reg16, src: Reg16, imm: u16 => (RMI) Prefix(0x66), Op(0x69), ModRm, Imm16;
+    dst: Reg32, src: Reg32, imm: u32 => (RMI)               Op(0x69), ModRm, Imm32;
+    dst: Reg64, src: Reg64, imm: u32 => (RMI) RexW,         Op(0x69), ModRm, Imm32;
+
+    <P: Mem> dst: Reg16, src: Word<P>,  imm: u8  => (RMI) Prefix(0x66), Op(0x6b), ModRm, Imm8;
+    <P: Mem> dst: Reg32, src: DWord<P>, imm: u8  => (RMI)               Op(0x6b), ModRm, Imm8;
+    <P: Mem> dst: Reg64, src: QWord<P>, imm: u8  => (RMI) RexW,         Op(0x6b), ModRm, Imm8;
+    <P: Mem> dst: Reg16, src: Word<P>,  imm: u16 => (RMI) Prefix(0x66), Op(0x69), ModRm, Imm16;
+    <P: Mem> dst: Reg32, src: DWord<P>, imm: u32 => (RMI)               Op(0x69), ModRm, Imm32;
+    <P: Mem> dst: Reg64, src: QWord<P>, imm: u32 => (RMI) RexW,         Op(0x69), ModRm, Imm32;
+}}
+
 pub trait Inc<T>: EmitSlice {
     fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
 }
@@ -515,6 +833,37 @@ op! { Dec {
 }}
 
 
+macro_rules! lock_unary_op {
+    ($( ($Op:ident, $op8:expr, $op:expr, $index:expr) ),*) => {
+        $(
+            impl<W> $Op<Lock<Operand>> for W where W: EmitSlice {
+                fn emit(&mut self, arg: Lock<Operand>) -> Result<(), Error<Self::Error>> {
+                    use operand::Operand::*;
+                    match arg.0 {
+                        BytePointer(a) => $Op::emit(self, Lock(a)),
+                        WordPointer(a) => $Op::emit(self, Lock(a)),
+                        DWordPointer(a) => $Op::emit(self, Lock(a)),
+                        QWordPointer(a) => $Op::emit(self, Lock(a)),
+                        _ => Err(Error::InvalidOperands),
+                    }
+                }
+            }
+
+            op! { $Op {
+                <P: Mem> p: Lock<Byte<P>>  => (M)               Op($op8), ModRmIndex($index);
+                <P: Mem> p: Lock<Word<P>>  => (M) Prefix(0x66), Op($op),  ModRmIndex($index);
+                <P: Mem> p: Lock<DWord<P>> => (M)               Op($op),  ModRmIndex($index);
+                <P: Mem> p: Lock<QWord<P>> => (M) RexW,         Op($op),  ModRmIndex($index);
+            }}
+        )*
+    };
+}
+
+lock_unary_op! {
+    (Not, 0xf6, 0xf7, 2), (Neg, 0xf6, 0xf7, 3),
+    (Inc, 0xfe, 0xff, 0), (Dec, 0xfe, 0xff, 1)
+}
+
 pub trait Test<D, S>: EmitSlice {
     fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
 }
@@ -649,6 +998,23 @@ op! { Mov {
     <P: Mem> p: QWord<P>, r: Reg64 => (MR) RexW,         Op(0x89), ModRm;
 }}
 
+rip_label_op! { Mov {
+    r: Reg8,  [p: Byte];
+    r: Reg16, [p: Word];
+    r: Reg32, [p: DWord];
+    r: Reg64, [p: QWord];
+
+    [p: Byte],  imm: u8  => 1;
+    [p: Word],  imm: u16 => 2;
+    [p: DWord], imm: u32 => 4;
+    [p: QWord], imm: u32 => 4;
+
+    [p: Byte],  r: Reg8  => 0;
+    [p: Word],  r: Reg16 => 0;
+    [p: DWord], r: Reg32 => 0;
+    [p: QWord], r: Reg64 => 0;
+}}
+
 
 pub trait Push<S>: EmitSlice {
     fn emit(&mut self, src: S) -> Result<(), Error<Self::Error>>;
@@ -718,6 +1084,7 @@ impl<W> Call<Operand> for W where W: EmitSlice {
         match arg {
             Offset32(a) => Call::emit(self, a),
             Reg64(a) => Call::emit(self, a),
+            QWordPointer(a) => Call::emit(self, a),
             _ => Err(Error::InvalidOperands),
         }
     }
@@ -743,8 +1110,15 @@ impl<W, L> Call<&mut L> for W
     }
 }
 
+impl Call<relax::Label> for Relax {
+    fn emit(&mut self, label: relax::Label) -> Result<(), Error<Self::Error>> {
+        self.push_call(label)
+    }
+}
+
 op! { Call {
     r: Reg64 => (M) Op(0xff), ModRmIndex(2);
+    <P: Mem> p: QWord<P> => (M) Op(0xff), ModRmIndex(2);
 }}
 
 
@@ -759,6 +1133,7 @@ impl<W> Jmp<Operand> for W where W: EmitSlice {
             Offset8(a) => Jmp::emit(self, a),
             Offset32(a) => Jmp::emit(self, a),
             Reg64(a) => Jmp::emit(self, a),
+            QWordPointer(a) => Jmp::emit(self, a),
             _ => Err(Error::InvalidOperands),
         }
     }
@@ -776,6 +1151,13 @@ impl<W> Jmp<i32> for W where W: EmitSlice {
     }
 }
 
+/// Returns the rel8 of a 2 byte branch to a label at `offset` from the start
+/// of the branch, if the label is bound behind the branch and close enough.
+/// Unbound labels give an offset of 0 and always get a rel32 fixup.
+fn backward_rel8(offset: i64) -> Option<i8> {
+    i8::try_from(offset - 2).ok().filter(|_| offset < 0)
+}
+
 impl<W, L> Jmp<&mut L> for W
     where W: Emit, L: Label<W, FixupKind>
 {
@@ -784,212 +1166,408 @@ impl<W, L> Jmp<&mut L> for W
             label,
             FixupKind::PcRel32,
             |emit, offset| {
-                Jmp::emit(emit, offset as i32)
+                if let Some(rel) = backward_rel8(offset) {
+                    Encode::<D, _>::encode(emit, rel, (Op(0xeb), Imm8))
+                } else {
+                    Jmp::emit(emit, offset as i32)
+                }
             },
         )
     }
 }
 
+impl Jmp<relax::Label> for Relax {
+    fn emit(&mut self, label: relax::Label) -> Result<(), Error<Self::Error>> {
+        self.push_jmp(label)
+    }
+}
+
 op! { Jmp {
     r: Reg64 => (M) Op(0xff), ModRmIndex(4);
+    <P: Mem> p: QWord<P> => (M) Op(0xff), ModRmIndex(4);
 }}
 
 
-pub trait Ret: EmitSlice {
-    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+/// Returns the rel32 offset from the current address to `addr` for a 5 byte
+/// call or jmp, if the final address is known and `addr` is within reach.
+fn rel32_offset<W>(emitter: &W, addr: u64) -> Option<i32> where W: Address {
+    let offset = (addr as i64).wrapping_sub(emitter.address()? as i64);
+    i32::try_from(offset).ok().filter(|offset| offset.checked_sub(5).is_some())
 }
 
-op! { Ret {
-    => (None) Op(0xc3);
-}}
-
+/// Calls an absolute address, either directly with a rel32 offset or
+/// through `scratch` if the target is out of reach or the final address of
+/// the code isn't known.
+pub trait CallAbs: EmitSlice {
+    fn emit(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>>;
+}
 
-macro_rules! cc_op {
-    ($( ($cond:ident, $Cmov:ident, $J:ident, $Set:ident) ),*) => {
-        $(
-        pub trait $Cmov<D, S>: EmitSlice {
-            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+impl<W> CallAbs for W where W: EmitSlice + Address {
+    fn emit(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>> {
+        if let Some(offset) = rel32_offset(self, addr) {
+            Call::emit(self, offset)
+        } else {
+            Mov::emit(self, scratch, addr)?;
+            Call::emit(self, scratch)
         }
+    }
+}
 
-        impl<W> $Cmov<Operand, Operand> for W where W: EmitSlice {
-            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
-                use operand::Operand::*;
-                match (dst, src) {
-                    (Reg16(d), Reg16(s)) => $Cmov::emit(self, d, s),
-                    (Reg32(d), Reg32(s)) => $Cmov::emit(self, d, s),
-                    (Reg64(d), Reg64(s)) => $Cmov::emit(self, d, s),
-                    (Reg16(d), WordPointer(s)) => $Cmov::emit(self, d, s),
-                    (Reg32(d), DWordPointer(s)) => $Cmov::emit(self, d, s),
-                    (Reg64(d), QWordPointer(s)) => $Cmov::emit(self, d, s),
-                    _ => Err(Error::InvalidOperands),
-                }
-            }
+/// Jumps to an absolute address, see `CallAbs`.
+pub trait JmpAbs: EmitSlice {
+    fn emit(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> JmpAbs for W where W: EmitSlice + Address {
+    fn emit(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>> {
+        if let Some(offset) = rel32_offset(self, addr) {
+            Jmp::emit(self, offset)
+        } else {
+            Mov::emit(self, scratch, addr)?;
+            Jmp::emit(self, scratch)
         }
+    }
+}
 
-        op! { $Cmov {
-            dst: Reg16, src: Reg16 => (RM) Prefix(0x66), Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
-            dst: Reg32, src: Reg32 => (RM)               Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
-            dst: Reg64, src: Reg64 => (RM) RexW,         Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
 
-            <P: Mem> dst: Reg16, src: Word<P> =>
-                (RM) Prefix(0x66), Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
-            <P: Mem> dst: Reg32, src: DWord<P> =>
-                (RM) Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
-            <P: Mem> dst: Reg64, src: QWord<P> =>
-                (RM) RexW, Op(0x0f), Op(0x40 | cond::$cond.0), ModRm;
-        }}
+pub trait JmpShort<T>: EmitSlice {
+    fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+}
 
+impl<W, L> JmpShort<&mut L> for W
+    where W: Emit, L: Label<W, FixupKind>
+{
+    fn emit(&mut self, label: &mut L) -> Result<(), Error<Self::Error>> {
+        self.emit_branch(
+            label,
+            FixupKind::PcRel8,
+            |emit, offset| match i8::try_from(offset - 2) {
+                Ok(rel) => Encode::<D, _>::encode(emit, rel, (Op(0xeb), Imm8)),
+                Err(_) => Err(Error::BranchOutOfRange { offset: offset - 2 }),
+            },
+        )
+    }
+}
 
-        pub trait $J<T>: EmitSlice {
-            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
-        }
 
-        impl<W> $J<Operand> for W where W: EmitSlice {
-            fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
-                use operand::Operand::*;
-                match arg {
-                    Offset8(a) => $J::emit(self, a),
-                    Offset32(a) => $J::emit(self, a),
-                    _ => Err(Error::InvalidOperands),
-                }
-            }
-        }
+pub trait Ret: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
 
-        impl<W> $J<i8> for W where W: EmitSlice {
-            fn emit(&mut self, imm: i8) -> Result<(), Error<Self::Error>> {
-                Encode::<D, _>::encode(self, imm - 2, (Op(0x70 | cond::$cond.0), Imm8))
-            }
-        }
+op! { Ret {
+    => (None) Op(0xc3);
+}}
 
-        impl<W> $J<i32> for W where W: EmitSlice {
-            fn emit(&mut self, imm: i32) -> Result<(), Error<Self::Error>> {
-                Encode::<D, _>::encode(self, imm - 6, (Op(0x0f), Op(0x80 | cond::$cond.0), Imm32))
-            }
-        }
+/// Returns and pops `imm` additional bytes of arguments off the stack.
+pub trait RetImm<T>: EmitSlice {
+    fn emit(&mut self, imm: T) -> Result<(), Error<Self::Error>>;
+}
 
-        impl<W, L> $J<&mut L> for W
-            where W: Emit, L: Label<W, FixupKind>
-        {
-            fn emit(&mut self, label: &mut L) -> Result<(), Error<Self::Error>> {
-                self.emit_branch(
-                    label,
-                    FixupKind::PcRel32,
-                    |emit, offset| {
-                        $J::emit(emit, offset as i32)
-                    },
-                )
-            }
+impl<W> RetImm<Operand> for W where W: EmitSlice {
+    fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
+        match arg {
+            Operand::Imm16(a) => RetImm::emit(self, a),
+            _ => Err(Error::InvalidOperands),
         }
+    }
+}
 
+op! { RetImm {
+    imm: u16 => (I) Op(0xc2), Imm16;
+}}
 
-        pub trait $Set<D>: EmitSlice {
-            fn emit(&mut self, dst: D) -> Result<(), Error<Self::Error>>;
-        }
+pub trait Leave: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
 
-        impl<W> $Set<Operand> for W where W: EmitSlice {
-            fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
-                use operand::Operand::*;
-                match arg {
-                    Reg8(a) => $Set::emit(self, a),
-                    BytePointer(a) => $Set::emit(self, a),
-                    _ => Err(Error::InvalidOperands),
-                }
-            }
-        }
+op! { Leave {
+    => (None) Op(0xc9);
+}}
 
-        op! { $Set {
-            r: Reg8 => (M) Op(0x0f), Op(0x90 | cond::$cond.0), ModRmIndex(0);
-            <P: Mem> p: Byte<P> => (M) Op(0x0f), Op(0x90 | cond::$cond.0), ModRmIndex(0);
-        }}
-        )*
-    };
+pub trait Enter<S, L>: EmitSlice {
+    fn emit(&mut self, size: S, level: L) -> Result<(), Error<Self::Error>>;
 }
 
-cc_op! {
-    (A,   Cmova,   Ja,   Seta),
-    (AE,  Cmovae,  Jae,  Setae),
-    (B,   Cmovb,   Jb,   Setb),
-    (BE,  Cmovbe,  Jbe,  Setbe),
-    (C,   Cmovc,   Jc,   Setc),
-    (E,   Cmove,   Je,   Sete),
-    (G,   Cmovg,   Jg,   Setg),
-    (GE,  Cmovge,  Jge,  Setge),
-    (L,   Cmovl,   Jl,   Setl),
-    (LE,  Cmovle,  Jle,  Setle),
-    (NA,  Cmovna,  Jna,  Setna),
-    (NAE, Cmovnae, Jnae, Setnae),
-    (NB,  Cmovnb,  Jnb,  Setnb),
-    (NBE, Cmovnbe, Jnbe, Setnbe),
-    (NC,  Cmovnc,  Jnc,  Setnc),
-    (NE,  Cmovne,  Jne,  Setne),
-    (NG,  Cmovng,  Jng,  Setng),
-    (NGE, Cmovnge, Jnge, Setnge),
-    (NL,  Cmovnl,  Jnl,  Setnl),
-    (NLE, Cmovnle, Jnle, Setnle),
-    (NO,  Cmovno,  Jno,  Setno),
-    (NP,  Cmovnp,  Jnp,  Setnp),
-    (NS,  Cmovns,  Jns,  Setns),
-    (NZ,  Cmovnz,  Jnz,  Setnz),
-    (O,   Cmovo,   Jo,   Seto),
-    (P,   Cmovp,   Jp,   Setp),
-    (PE,  Cmovpe,  Jpe,  Setpe),
-    (PO,  Cmovpo,  Jpo,  Setpo),
-    (S,   Cmovs,   Js,   Sets),
-    (Z,   Cmovz,   Jz,   Setz)
+impl<W> Enter<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, size: Operand, level: Operand) -> Result<(), Error<Self::Error>> {
+        match (size, level) {
+            (Operand::Imm16(a), Operand::Imm8(b)) => Enter::emit(self, a, b),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
 }
 
+op! { Enter {
+    size: u16, level: u8 => (I) Op(0xc8), Imm16, Imm8;
+}}
 
-pub trait Lea<D, S>: EmitSlice {
-    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+
+pub trait Cmovcc<D, S>: EmitSlice {
+    fn emit(&mut self, cond: Cond, dst: D, src: S) -> Result<(), Error<Self::Error>>;
 }
 
-impl<W> Lea<Operand, Operand> for W where W: EmitSlice {
-    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+impl<W> Cmovcc<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, cond: Cond, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
         use operand::Operand::*;
         match (dst, src) {
-            (Reg16(d), WordPointer(s)) => Lea::emit(self, d, s),
-            (Reg32(d), DWordPointer(s)) => Lea::emit(self, d, s),
-            (Reg64(d), QWordPointer(s)) => Lea::emit(self, d, s),
+            (Reg16(d), Reg16(s)) => Cmovcc::emit(self, cond, d, s),
+            (Reg32(d), Reg32(s)) => Cmovcc::emit(self, cond, d, s),
+            (Reg64(d), Reg64(s)) => Cmovcc::emit(self, cond, d, s),
+            (Reg16(d), WordPointer(s)) => Cmovcc::emit(self, cond, d, s),
+            (Reg32(d), DWordPointer(s)) => Cmovcc::emit(self, cond, d, s),
+            (Reg64(d), QWordPointer(s)) => Cmovcc::emit(self, cond, d, s),
             _ => Err(Error::InvalidOperands),
         }
     }
 }
 
-op! { Lea {
-    <P: Mem> dst: Reg16, p: Word<P>  => (RM) Prefix(0x66), Op(0x8d), ModRm;
-    <P: Mem> dst: Reg32, p: DWord<P> => (RM)               Op(0x8d), ModRm;
-    <P: Mem> dst: Reg64, p: QWord<P> => (RM) RexW,         Op(0x8d), ModRm;
+cond_op! { Cmovcc(cond) {
+    dst: Reg16, src: Reg16 => (RM) Prefix(0x66), Op(0x0f), Op(0x40 | cond.code()), ModRm;
+    dst: Reg32, src: Reg32 => (RM)               Op(0x0f), Op(0x40 | cond.code()), ModRm;
+    dst: Reg64, src: Reg64 => (RM) RexW,         Op(0x0f), Op(0x40 | cond.code()), ModRm;
+
+    <P: Mem> dst: Reg16, src: Word<P> =>
+        (RM) Prefix(0x66), Op(0x0f), Op(0x40 | cond.code()), ModRm;
+    <P: Mem> dst: Reg32, src: DWord<P> =>
+        (RM) Op(0x0f), Op(0x40 | cond.code()), ModRm;
+    <P: Mem> dst: Reg64, src: QWord<P> =>
+        (RM) RexW, Op(0x0f), Op(0x40 | cond.code()), ModRm;
 }}
 
 
-pub trait Movzx<D, S>: EmitSlice {
-    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+pub trait Jcc<T>: EmitSlice {
+    fn emit(&mut self, cond: Cond, arg: T) -> Result<(), Error<Self::Error>>;
 }
 
-impl<W> Movzx<Operand, Operand> for W where W: EmitSlice {
-    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+impl<W> Jcc<Operand> for W where W: EmitSlice {
+    fn emit(&mut self, cond: Cond, arg: Operand) -> Result<(), Error<Self::Error>> {
         use operand::Operand::*;
-        match (dst, src) {
-            (Reg16(d), Reg8(s)) => Movzx::emit(self, d, s),
-            (Reg32(d), Reg8(s)) => Movzx::emit(self, d, s),
-            (Reg64(d), Reg8(s)) => Movzx::emit(self, d, s),
-            (Reg32(d), Reg16(s)) => Movzx::emit(self, d, s),
-            (Reg64(d), Reg16(s)) => Movzx::emit(self, d, s),
-            (Reg16(d), BytePointer(s)) => Movzx::emit(self, d, s),
-            (Reg32(d), BytePointer(s)) => Movzx::emit(self, d, s),
-            (Reg64(d), BytePointer(s)) => Movzx::emit(self, d, s),
-            (Reg32(d), WordPointer(s)) => Movzx::emit(self, d, s),
-            (Reg64(d), WordPointer(s)) => Movzx::emit(self, d, s),
+        match arg {
+            Offset8(a) => Jcc::emit(self, cond, a),
+            Offset32(a) => Jcc::emit(self, cond, a),
             _ => Err(Error::InvalidOperands),
         }
     }
 }
 
-op! { Movzx {
-    dst: Reg16, src: Reg8 => (RM) Prefix(0x66), Op(0x0f), Op(0xb6), ModRm;
-    dst: Reg32, src: Reg8 => (RM)               Op(0x0f), Op(0xb6), ModRm;
-    dst: Reg64, src: Reg8 => (RM) RexW,         Op(0x0f), Op(0xb6), ModRm;
-    dst: Reg32, src: Reg16 => (RM)       Op(0x0f), Op(0xb7), ModRm;
+impl<W> Jcc<i8> for W where W: EmitSlice {
+    fn emit(&mut self, cond: Cond, imm: i8) -> Result<(), Error<Self::Error>> {
+        Encode::<D, _>::encode(self, imm - 2, (Op(0x70 | cond.code()), Imm8))
+    }
+}
+
+impl<W> Jcc<i32> for W where W: EmitSlice {
+    fn emit(&mut self, cond: Cond, imm: i32) -> Result<(), Error<Self::Error>> {
+        Encode::<D, _>::encode(self, imm - 6, (Op(0x0f), Op(0x80 | cond.code()), Imm32))
+    }
+}
+
+impl<W, L> Jcc<&mut L> for W
+    where W: Emit, L: Label<W, FixupKind>
+{
+    fn emit(&mut self, cond: Cond, label: &mut L) -> Result<(), Error<Self::Error>> {
+        self.emit_branch(
+            label,
+            FixupKind::PcRel32,
+            |emit, offset| {
+                if let Some(rel) = backward_rel8(offset) {
+                    Encode::<D, _>::encode(emit, rel, (Op(0x70 | cond.code()), Imm8))
+                } else {
+                    Jcc::emit(emit, cond, offset as i32)
+                }
+            },
+        )
+    }
+}
+
+impl Jcc<relax::Label> for Relax {
+    fn emit(&mut self, cond: Cond, label: relax::Label) -> Result<(), Error<Self::Error>> {
+        self.push_jcc(cond, label)
+    }
+}
+
+
+pub trait JccShort<T>: EmitSlice {
+    fn emit(&mut self, cond: Cond, arg: T) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W, L> JccShort<&mut L> for W
+    where W: Emit, L: Label<W, FixupKind>
+{
+    fn emit(&mut self, cond: Cond, label: &mut L) -> Result<(), Error<Self::Error>> {
+        self.emit_branch(
+            label,
+            FixupKind::PcRel8,
+            |emit, offset| match i8::try_from(offset - 2) {
+                Ok(rel) => Encode::<D, _>::encode(emit, rel, (Op(0x70 | cond.code()), Imm8)),
+                Err(_) => Err(Error::BranchOutOfRange { offset: offset - 2 }),
+            },
+        )
+    }
+}
+
+
+pub trait Setcc<D>: EmitSlice {
+    fn emit(&mut self, cond: Cond, dst: D) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Setcc<Operand> for W where W: EmitSlice {
+    fn emit(&mut self, cond: Cond, arg: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match arg {
+            Reg8(a) => Setcc::emit(self, cond, a),
+            BytePointer(a) => Setcc::emit(self, cond, a),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+cond_op! { Setcc(cond) {
+    r: Reg8 => (M) Op(0x0f), Op(0x90 | cond.code()), ModRmIndex(0);
+    <P: Mem> p: Byte<P> => (M) Op(0x0f), Op(0x90 | cond.code()), ModRmIndex(0);
+}}
+
+
+macro_rules! cc_op {
+    ($( ($cond:ident, $Cmov:ident, $J:ident, $JShort:ident, $Set:ident) ),*) => {
+        $(
+        pub trait $Cmov<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W, D, S> $Cmov<D, S> for W where W: Cmovcc<D, S> {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>> {
+                Cmovcc::emit(self, cond::$cond, dst, src)
+            }
+        }
+
+
+        pub trait $J<T>: EmitSlice {
+            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W, T> $J<T> for W where W: Jcc<T> {
+            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>> {
+                Jcc::emit(self, cond::$cond, arg)
+            }
+        }
+
+
+        pub trait $JShort<T>: EmitSlice {
+            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W, T> $JShort<T> for W where W: JccShort<T> {
+            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>> {
+                JccShort::emit(self, cond::$cond, arg)
+            }
+        }
+
+
+        pub trait $Set<D>: EmitSlice {
+            fn emit(&mut self, dst: D) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W, D> $Set<D> for W where W: Setcc<D> {
+            fn emit(&mut self, dst: D) -> Result<(), Error<Self::Error>> {
+                Setcc::emit(self, cond::$cond, dst)
+            }
+        }
+        )*
+    };
+}
+
+cc_op! {
+    (A,   Cmova,   Ja,   JaShort,   Seta),
+    (AE,  Cmovae,  Jae,  JaeShort,  Setae),
+    (B,   Cmovb,   Jb,   JbShort,   Setb),
+    (BE,  Cmovbe,  Jbe,  JbeShort,  Setbe),
+    (C,   Cmovc,   Jc,   JcShort,   Setc),
+    (E,   Cmove,   Je,   JeShort,   Sete),
+    (G,   Cmovg,   Jg,   JgShort,   Setg),
+    (GE,  Cmovge,  Jge,  JgeShort,  Setge),
+    (L,   Cmovl,   Jl,   JlShort,   Setl),
+    (LE,  Cmovle,  Jle,  JleShort,  Setle),
+    (NA,  Cmovna,  Jna,  JnaShort,  Setna),
+    (NAE, Cmovnae, Jnae, JnaeShort, Setnae),
+    (NB,  Cmovnb,  Jnb,  JnbShort,  Setnb),
+    (NBE, Cmovnbe, Jnbe, JnbeShort, Setnbe),
+    (NC,  Cmovnc,  Jnc,  JncShort,  Setnc),
+    (NE,  Cmovne,  Jne,  JneShort,  Setne),
+    (NG,  Cmovng,  Jng,  JngShort,  Setng),
+    (NGE, Cmovnge, Jnge, JngeShort, Setnge),
+    (NL,  Cmovnl,  Jnl,  JnlShort,  Setnl),
+    (NLE, Cmovnle, Jnle, JnleShort, Setnle),
+    (NO,  Cmovno,  Jno,  JnoShort,  Setno),
+    (NP,  Cmovnp,  Jnp,  JnpShort,  Setnp),
+    (NS,  Cmovns,  Jns,  JnsShort,  Setns),
+    (NZ,  Cmovnz,  Jnz,  JnzShort,  Setnz),
+    (O,   Cmovo,   Jo,   JoShort,   Seto),
+    (P,   Cmovp,   Jp,   JpShort,   Setp),
+    (PE,  Cmovpe,  Jpe,  JpeShort,  Setpe),
+    (PO,  Cmovpo,  Jpo,  JpoShort,  Setpo),
+    (S,   Cmovs,   Js,   JsShort,   Sets),
+    (Z,   Cmovz,   Jz,   JzShort,   Setz)
+}
+
+
+pub trait Lea<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Lea<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Reg16(d), WordPointer(s)) => Lea::emit(self, d, s),
+            (Reg32(d), DWordPointer(s)) => Lea::emit(self, d, s),
+            (Reg64(d), QWordPointer(s)) => Lea::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Lea {
+    <P: Mem> dst: Reg16, p: Word<P>  => (RM) Prefix(0x66), Op(0x8d), ModRm;
+    <P: Mem> dst: Reg32, p: DWord<P> => (RM)               Op(0x8d), ModRm;
+    <P: Mem> dst: Reg64, p: QWord<P> => (RM) RexW,         Op(0x8d), ModRm;
+}}
+
+rip_label_op! { Lea {
+    dst: Reg16, [p: Word];
+    dst: Reg32, [p: DWord];
+    dst: Reg64, [p: QWord];
+}}
+
+
+pub trait Movzx<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Movzx<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Reg16(d), Reg8(s)) => Movzx::emit(self, d, s),
+            (Reg32(d), Reg8(s)) => Movzx::emit(self, d, s),
+            (Reg64(d), Reg8(s)) => Movzx::emit(self, d, s),
+            (Reg32(d), Reg16(s)) => Movzx::emit(self, d, s),
+            (Reg64(d), Reg16(s)) => Movzx::emit(self, d, s),
+            (Reg16(d), BytePointer(s)) => Movzx::emit(self, d, s),
+            (Reg32(d), BytePointer(s)) => Movzx::emit(self, d, s),
+            (Reg64(d), BytePointer(s)) => Movzx::emit(self, d, s),
+            (Reg32(d), WordPointer(s)) => Movzx::emit(self, d, s),
+            (Reg64(d), WordPointer(s)) => Movzx::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Movzx {
+    dst: Reg16, src: Reg8 => (RM) Prefix(0x66), Op(0x0f), Op(0xb6), ModRm;
+    dst: Reg32, src: Reg8 => (RM)               Op(0x0f), Op(0xb6), ModRm;
+    dst: Reg64, src: Reg8 => (RM) RexW,         Op(0x0f), Op(0xb6), ModRm;
+    dst: Reg32, src: Reg16 => (RM)       Op(0x0f), Op(0xb7), ModRm;
     dst: Reg64, src: Reg16 => (RM) RexW, Op(0x0f), Op(0xb7), ModRm;
 
     <P: Mem> dst: Reg16, src: Byte<P> => (RM) Prefix(0x66), Op(0x0f), Op(0xb6), ModRm;
@@ -1098,6 +1676,130 @@ op! { Bsr {
 }}
 
 
+macro_rules! bit_test_op {
+    ($( ($Op:ident, $op:expr, $index:expr) ),*) => {
+        $(
+        /// With a register bit index and a memory operand, the bit offset
+        /// is signed and not limited to the operand size, so it addresses
+        /// a bit string starting at the memory operand.
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Reg16(d), Reg16(s)) => $Op::emit(self, d, s),
+                    (Reg32(d), Reg32(s)) => $Op::emit(self, d, s),
+                    (Reg64(d), Reg64(s)) => $Op::emit(self, d, s),
+                    (Reg16(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (Reg32(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (Reg64(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (WordPointer(d), Reg16(s)) => $Op::emit(self, d, s),
+                    (DWordPointer(d), Reg32(s)) => $Op::emit(self, d, s),
+                    (QWordPointer(d), Reg64(s)) => $Op::emit(self, d, s),
+                    (WordPointer(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (DWordPointer(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (QWordPointer(d), Imm8(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Reg16, src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op), ModRm;
+            dst: Reg32, src: Reg32 => (MR)               Op(0x0f), Op($op), ModRm;
+            dst: Reg64, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op), ModRm;
+
+            dst: Reg16, src: u8 => (MI) Prefix(0x66), Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            dst: Reg32, src: u8 => (MI)               Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            dst: Reg64, src: u8 => (MI) RexW,         Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+
+            <P: Mem> dst: Word<P>,  src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: DWord<P>, src: Reg32 => (MR)               Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: QWord<P>, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op), ModRm;
+
+            <P: Mem> dst: Word<P>,  src: u8 => (MI) Prefix(0x66), Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            <P: Mem> dst: DWord<P>, src: u8 => (MI)               Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            <P: Mem> dst: QWord<P>, src: u8 => (MI) RexW,         Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+        }}
+        )*
+    };
+}
+
+bit_test_op! {
+    (Bt, 0xa3, 4), (Bts, 0xab, 5), (Btr, 0xb3, 6), (Btc, 0xbb, 7)
+}
+
+
+macro_rules! lock_bit_test_op {
+    ($( ($Op:ident, $op:expr, $index:expr) ),*) => {
+        $(
+        impl<W> $Op<Lock<Operand>, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Lock<Operand>, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst.0, src) {
+                    (WordPointer(d), Reg16(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Reg32(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Reg64(s)) => $Op::emit(self, Lock(d), s),
+                    (WordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Imm8(s)) => $Op::emit(self, Lock(d), s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            <P: Mem> dst: Lock<Word<P>>,  src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Lock<DWord<P>>, src: Reg32 => (MR)               Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Lock<QWord<P>>, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op), ModRm;
+
+            <P: Mem> dst: Lock<Word<P>>,  src: u8 => (MI) Prefix(0x66), Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            <P: Mem> dst: Lock<DWord<P>>, src: u8 => (MI)               Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+            <P: Mem> dst: Lock<QWord<P>>, src: u8 => (MI) RexW,         Op(0x0f), Op(0xba), ModRmIndex($index), Imm8;
+        }}
+        )*
+    };
+}
+
+lock_bit_test_op! {
+    (Bts, 0xab, 5), (Btr, 0xb3, 6), (Btc, 0xbb, 7)
+}
+
+pub trait Cbw: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Cbw {
+    => (None) Prefix(0x66), Op(0x98);
+}}
+
+pub trait Cwde: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Cwde {
+    => (None) Op(0x98);
+}}
+
+pub trait Cdqe: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Cdqe {
+    => (None) RexW, Op(0x98);
+}}
+
+pub trait Cwd: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Cwd {
+    => (None) Prefix(0x66), Op(0x99);
+}}
+
 pub trait Cdq: EmitSlice {
     fn emit(&mut self) -> Result<(), Error<Self::Error>>;
 }
@@ -1106,6 +1808,14 @@ op! { Cdq {
     => (None) Op(0x99);
 }}
 
+pub trait Cqo: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Cqo {
+    => (None) RexW, Op(0x99);
+}}
+
 
 pub trait Xchg<D, S>: EmitSlice {
     fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
@@ -1171,10 +1881,1085 @@ op! { Xchg {
 }}
 
 
-pub trait Ud2: EmitSlice {
-    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+macro_rules! exchange_op {
+    ($( ($Op:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Reg8(d), Reg8(s)) => $Op::emit(self, d, s),
+                    (Reg16(d), Reg16(s)) => $Op::emit(self, d, s),
+                    (Reg32(d), Reg32(s)) => $Op::emit(self, d, s),
+                    (Reg64(d), Reg64(s)) => $Op::emit(self, d, s),
+                    (BytePointer(d), Reg8(s)) => $Op::emit(self, d, s),
+                    (WordPointer(d), Reg16(s)) => $Op::emit(self, d, s),
+                    (DWordPointer(d), Reg32(s)) => $Op::emit(self, d, s),
+                    (QWordPointer(d), Reg64(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        impl<W> $Op<Lock<Operand>, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Lock<Operand>, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst.0, src) {
+                    (BytePointer(d), Reg8(s)) => $Op::emit(self, Lock(d), s),
+                    (WordPointer(d), Reg16(s)) => $Op::emit(self, Lock(d), s),
+                    (DWordPointer(d), Reg32(s)) => $Op::emit(self, Lock(d), s),
+                    (QWordPointer(d), Reg64(s)) => $Op::emit(self, Lock(d), s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Reg8,  src: Reg8  => (MR)               Op(0x0f), Op($op),     ModRm;
+            dst: Reg16, src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op + 1), ModRm;
+            dst: Reg32, src: Reg32 => (MR)               Op(0x0f), Op($op + 1), ModRm;
+            dst: Reg64, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op + 1), ModRm;
+
+            <P: Mem> dst: Byte<P>,  src: Reg8  => (MR)               Op(0x0f), Op($op),     ModRm;
+            <P: Mem> dst: Word<P>,  src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: DWord<P>, src: Reg32 => (MR)               Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: QWord<P>, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op + 1), ModRm;
+
+            <P: Mem> dst: Lock<Byte<P>>,  src: Reg8  => (MR)               Op(0x0f), Op($op),     ModRm;
+            <P: Mem> dst: Lock<Word<P>>,  src: Reg16 => (MR) Prefix(0x66), Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: Lock<DWord<P>>, src: Reg32 => (MR)               Op(0x0f), Op($op + 1), ModRm;
+            <P: Mem> dst: Lock<QWord<P>>, src: Reg64 => (MR) RexW,         Op(0x0f), Op($op + 1), ModRm;
+        }}
+        )*
+    };
 }
 
-op! { Ud2 {
-    => (None) Op(0x0f), Op(0x0b);
+exchange_op! {
+    (Xadd, 0xc0), (Cmpxchg, 0xb0)
+}
+
+
+pub trait Cmpxchg8b<T>: EmitSlice {
+    fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Cmpxchg8b<Operand> for W where W: EmitSlice {
+    fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
+        match arg {
+            Operand::QWordPointer(a) => Cmpxchg8b::emit(self, a),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+impl<W> Cmpxchg8b<Lock<Operand>> for W where W: EmitSlice {
+    fn emit(&mut self, arg: Lock<Operand>) -> Result<(), Error<Self::Error>> {
+        match arg.0 {
+            Operand::QWordPointer(a) => Cmpxchg8b::emit(self, Lock(a)),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Cmpxchg8b {
+    <P: Mem> p: QWord<P> => (M) Op(0x0f), Op(0xc7), ModRmIndex(1);
+    <P: Mem> p: Lock<QWord<P>> => (M) Op(0x0f), Op(0xc7), ModRmIndex(1);
+}}
+
+
+pub trait Cmpxchg16b<T>: EmitSlice {
+    fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Cmpxchg16b<Operand> for W where W: EmitSlice {
+    fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
+        match arg {
+            Operand::XmmWordPointer(a) => Cmpxchg16b::emit(self, a),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+impl<W> Cmpxchg16b<Lock<Operand>> for W where W: EmitSlice {
+    fn emit(&mut self, arg: Lock<Operand>) -> Result<(), Error<Self::Error>> {
+        match arg.0 {
+            Operand::XmmWordPointer(a) => Cmpxchg16b::emit(self, Lock(a)),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Cmpxchg16b {
+    <P: Mem> p: XmmWord<P> => (M) RexW, Op(0x0f), Op(0xc7), ModRmIndex(1);
+    <P: Mem> p: Lock<XmmWord<P>> => (M) RexW, Op(0x0f), Op(0xc7), ModRmIndex(1);
+}}
+
+
+pub trait Ud2: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Ud2 {
+    => (None) Op(0x0f), Op(0x0b);
+}}
+
+
+macro_rules! string_op_size {
+    ($Op:ident, $Size:ident, [$($Rep:ident),*], $enc:tt) => {
+        impl<W> $Op<string::$Size> for W where W: EmitSlice {
+            fn emit(&mut self, _: string::$Size) -> Result<(), Error<Self::Error>> {
+                #![allow(unused_parens)]
+                Encode::<None, _>::encode(self, (), $enc)
+            }
+        }
+
+        $(
+        impl<W> $Op<string::$Rep<string::$Size>> for W where W: EmitSlice {
+            fn emit(&mut self, _: string::$Rep<string::$Size>) -> Result<(), Error<Self::Error>> {
+                #![allow(unused_parens)]
+                Encode::<None, _>::encode(self, string::$Rep(()), $enc)
+            }
+        }
+        )*
+    };
+}
+
+macro_rules! string_op {
+    ($( ($Op:ident, $op:expr, [$($Rep:ident),*]) ),*) => {
+        $(
+        pub trait $Op<S>: EmitSlice {
+            fn emit(&mut self, size: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        string_op_size!($Op, B, [$($Rep),*], (Op($op)));
+        string_op_size!($Op, W, [$($Rep),*], (Prefix(0x66), Op($op + 1)));
+        string_op_size!($Op, D, [$($Rep),*], (Op($op + 1)));
+        string_op_size!($Op, Q, [$($Rep),*], (RexW, Op($op + 1)));
+        )*
+    };
+}
+
+string_op! {
+    (Movs, 0xa4, [Rep]),
+    (Cmps, 0xa6, [Repe, Repne]),
+    (Stos, 0xaa, [Rep]),
+    (Lods, 0xac, [Rep]),
+    (Scas, 0xae, [Repe, Repne])
+}
+
+macro_rules! fence_op {
+    ($( ($Op:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op: EmitSlice {
+            fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+        }
+
+        op! { $Op {
+            => (None) Op(0x0f), Op(0xae), Op($op);
+        }}
+        )*
+    };
+}
+
+fence_op! {
+    (Lfence, 0xe8), (Mfence, 0xf0), (Sfence, 0xf8)
+}
+
+
+pub trait Pause: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Pause {
+    => (None) Prefix(0xf3), Op(0x90);
+}}
+
+
+/// The recommended NOP sequences of 1 to 11 bytes.
+const NOPS: [&[u8]; 11] = [
+    &[0x90],
+    &[0x66, 0x90],
+    &[0x0f, 0x1f, 0x00],
+    &[0x0f, 0x1f, 0x40, 0x00],
+    &[0x0f, 0x1f, 0x44, 0x00, 0x00],
+    &[0x66, 0x0f, 0x1f, 0x44, 0x00, 0x00],
+    &[0x0f, 0x1f, 0x80, 0x00, 0x00, 0x00, 0x00],
+    &[0x0f, 0x1f, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+    &[0x66, 0x0f, 0x1f, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+    &[0x66, 0x66, 0x0f, 0x1f, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+    &[0x66, 0x66, 0x66, 0x0f, 0x1f, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+];
+
+/// A single NOP instruction of `len` bytes, where `len` is between 1 and 11.
+pub trait Nop<T>: EmitSlice {
+    fn emit(&mut self, len: T) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Nop<u8> for W where W: EmitSlice {
+    fn emit(&mut self, len: u8) -> Result<(), Error<Self::Error>> {
+        match NOPS.get((len as usize).wrapping_sub(1)) {
+            Some(nop) => {
+                self.emit_slice(nop)?;
+                Ok(())
+            }
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+/// Pads with as few NOP instructions as possible up to the next multiple of
+/// `align` bytes.
+pub trait Align: EmitSlice {
+    fn emit(&mut self, align: u64) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Align for W where W: Emit {
+    fn emit(&mut self, align: u64) -> Result<(), Error<Self::Error>> {
+        if align == 0 {
+            return Err(Error::InvalidOperands);
+        }
+        let mut padding = (align - self.cur_pos() % align) % align;
+        while padding > 0 {
+            let len = padding.min(NOPS.len() as u64);
+            Nop::emit(self, len as u8)?;
+            padding -= len;
+        }
+        Ok(())
+    }
+}
+
+
+macro_rules! cache_flush_op {
+    ($( ($Op:ident, [$($prefix:expr),*], $index:expr) ),*) => {
+        $(
+        pub trait $Op<T>: EmitSlice {
+            fn emit(&mut self, arg: T) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand> for W where W: EmitSlice {
+            fn emit(&mut self, arg: Operand) -> Result<(), Error<Self::Error>> {
+                match arg {
+                    Operand::BytePointer(a) => $Op::emit(self, a),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            <P: Mem> p: Byte<P> => (M) $(Prefix($prefix),)* Op(0x0f), Op(0xae), ModRmIndex($index);
+        }}
+        )*
+    };
+}
+
+cache_flush_op! {
+    (Clflush, [], 7),
+    (Clflushopt, [0x66], 7),
+    (Clwb, [0x66], 6)
+}
+
+macro_rules! sse_op {
+    ($( ($Op:ident, [$($prefix:expr),*], $Size:ident, $SizePointer:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), $SizePointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src: Xmm => (RM) $($prefix,)* Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Xmm, src: $Size<P> => (RM) $($prefix,)* Op(0x0f), Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+sse_op! {
+    (Addss,    [Prefix(0xf3)], DWord, DWordPointer, 0x58),
+    (Addsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x58),
+    (Subss,    [Prefix(0xf3)], DWord, DWordPointer, 0x5c),
+    (Subsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x5c),
+    (Mulss,    [Prefix(0xf3)], DWord, DWordPointer, 0x59),
+    (Mulsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x59),
+    (Divss,    [Prefix(0xf3)], DWord, DWordPointer, 0x5e),
+    (Divsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x5e),
+    (Sqrtss,   [Prefix(0xf3)], DWord, DWordPointer, 0x51),
+    (Sqrtsd,   [Prefix(0xf2)], QWord, QWordPointer, 0x51),
+    (Minss,    [Prefix(0xf3)], DWord, DWordPointer, 0x5d),
+    (Minsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x5d),
+    (Maxss,    [Prefix(0xf3)], DWord, DWordPointer, 0x5f),
+    (Maxsd,    [Prefix(0xf2)], QWord, QWordPointer, 0x5f),
+    (Ucomiss,  [],             DWord, DWordPointer, 0x2e),
+    (Ucomisd,  [Prefix(0x66)], QWord, QWordPointer, 0x2e),
+    (Comiss,   [],             DWord, DWordPointer, 0x2f),
+    (Comisd,   [Prefix(0x66)], QWord, QWordPointer, 0x2f),
+    (Cvtss2sd, [Prefix(0xf3)], DWord, DWordPointer, 0x5a),
+    (Cvtsd2ss, [Prefix(0xf2)], QWord, QWordPointer, 0x5a),
+
+    (Addps, [],             XmmWord, XmmWordPointer, 0x58),
+    (Addpd, [Prefix(0x66)], XmmWord, XmmWordPointer, 0x58),
+    (Mulps, [],             XmmWord, XmmWordPointer, 0x59),
+    (Mulpd, [Prefix(0x66)], XmmWord, XmmWordPointer, 0x59),
+    (Andps, [],             XmmWord, XmmWordPointer, 0x54),
+    (Xorps, [],             XmmWord, XmmWordPointer, 0x57),
+
+    (Paddb,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xfc),
+    (Paddw,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xfd),
+    (Paddd,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xfe),
+    (Paddq,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xd4),
+    (Psubb,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xf8),
+    (Psubw,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xf9),
+    (Psubd,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xfa),
+    (Psubq,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xfb),
+    (Pand,       [Prefix(0x66)], XmmWord, XmmWordPointer, 0xdb),
+    (Pandn,      [Prefix(0x66)], XmmWord, XmmWordPointer, 0xdf),
+    (Por,        [Prefix(0x66)], XmmWord, XmmWordPointer, 0xeb),
+    (Pxor,       [Prefix(0x66)], XmmWord, XmmWordPointer, 0xef),
+    (Pcmpeqb,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x74),
+    (Pcmpeqw,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x75),
+    (Pcmpeqd,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x76),
+    (Pcmpgtb,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x64),
+    (Pcmpgtw,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x65),
+    (Pcmpgtd,    [Prefix(0x66)], XmmWord, XmmWordPointer, 0x66),
+    (Punpcklbw,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x60),
+    (Punpcklwd,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x61),
+    (Punpckldq,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x62),
+    (Punpcklqdq, [Prefix(0x66)], XmmWord, XmmWordPointer, 0x6c),
+    (Punpckhbw,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x68),
+    (Punpckhwd,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x69),
+    (Punpckhdq,  [Prefix(0x66)], XmmWord, XmmWordPointer, 0x6a),
+    (Punpckhqdq, [Prefix(0x66)], XmmWord, XmmWordPointer, 0x6d)
+}
+
+
+macro_rules! sse_move_op {
+    ($( ($Op:ident, [$($prefix:expr),*], $load:expr, $store:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), XmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (XmmWordPointer(d), Xmm(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src: Xmm => (RM) $($prefix,)* Op(0x0f), Op($load), ModRm;
+            <P: Mem> dst: Xmm, src: XmmWord<P> => (RM) $($prefix,)* Op(0x0f), Op($load), ModRm;
+            <P: Mem> dst: XmmWord<P>, src: Xmm => (MR) $($prefix,)* Op(0x0f), Op($store), ModRm;
+        }}
+        )*
+    };
+}
+
+sse_move_op! {
+    (Movaps, [],             0x28, 0x29),
+    (Movups, [],             0x10, 0x11),
+    (Movdqa, [Prefix(0x66)], 0x6f, 0x7f),
+    (Movdqu, [Prefix(0xf3)], 0x6f, 0x7f)
+}
+
+
+pub trait Movss<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Movss<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Xmm(d), Xmm(s)) => Movss::emit(self, d, s),
+            (Xmm(d), DWordPointer(s)) => Movss::emit(self, d, s),
+            (DWordPointer(d), Xmm(s)) => Movss::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Movss {
+    dst: Xmm, src: Xmm => (RM) Prefix(0xf3), Op(0x0f), Op(0x10), ModRm;
+    <P: Mem> dst: Xmm, src: DWord<P> => (RM) Prefix(0xf3), Op(0x0f), Op(0x10), ModRm;
+    <P: Mem> dst: DWord<P>, src: Xmm => (MR) Prefix(0xf3), Op(0x0f), Op(0x11), ModRm;
+}}
+
+
+pub trait Movsd<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Movsd<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Xmm(d), Xmm(s)) => Movsd::emit(self, d, s),
+            (Xmm(d), QWordPointer(s)) => Movsd::emit(self, d, s),
+            (QWordPointer(d), Xmm(s)) => Movsd::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Movsd {
+    dst: Xmm, src: Xmm => (RM) Prefix(0xf2), Op(0x0f), Op(0x10), ModRm;
+    <P: Mem> dst: Xmm, src: QWord<P> => (RM) Prefix(0xf2), Op(0x0f), Op(0x10), ModRm;
+    <P: Mem> dst: QWord<P>, src: Xmm => (MR) Prefix(0xf2), Op(0x0f), Op(0x11), ModRm;
+}}
+
+
+macro_rules! cvt_int_to_float_op {
+    ($( ($Op:ident, $prefix:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Reg32(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), Reg64(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), DWordPointer(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), QWordPointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src: Reg32 => (RM) Prefix($prefix),       Op(0x0f), Op(0x2a), ModRm;
+            dst: Xmm, src: Reg64 => (RM) Prefix($prefix), RexW, Op(0x0f), Op(0x2a), ModRm;
+            <P: Mem> dst: Xmm, src: DWord<P> =>
+                (RM) Prefix($prefix), Op(0x0f), Op(0x2a), ModRm;
+            <P: Mem> dst: Xmm, src: QWord<P> =>
+                (RM) Prefix($prefix), RexW, Op(0x0f), Op(0x2a), ModRm;
+        }}
+        )*
+    };
+}
+
+cvt_int_to_float_op! {
+    (Cvtsi2ss, 0xf3),
+    (Cvtsi2sd, 0xf2)
+}
+
+
+macro_rules! cvt_float_to_int_op {
+    ($( ($Op:ident, $prefix:expr, $Size:ident, $SizePointer:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Reg32(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Reg64(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Reg32(d), $SizePointer(s)) => $Op::emit(self, d, s),
+                    (Reg64(d), $SizePointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Reg32, src: Xmm => (RM) Prefix($prefix),       Op(0x0f), Op($op), ModRm;
+            dst: Reg64, src: Xmm => (RM) Prefix($prefix), RexW, Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Reg32, src: $Size<P> =>
+                (RM) Prefix($prefix), Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Reg64, src: $Size<P> =>
+                (RM) Prefix($prefix), RexW, Op(0x0f), Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+cvt_float_to_int_op! {
+    (Cvtss2si,  0xf3, DWord, DWordPointer, 0x2d),
+    (Cvtsd2si,  0xf2, QWord, QWordPointer, 0x2d),
+    (Cvttss2si, 0xf3, DWord, DWordPointer, 0x2c),
+    (Cvttsd2si, 0xf2, QWord, QWordPointer, 0x2c)
+}
+
+
+pub trait Movd<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Movd<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Xmm(d), Reg32(s)) => Movd::emit(self, d, s),
+            (Xmm(d), DWordPointer(s)) => Movd::emit(self, d, s),
+            (Reg32(d), Xmm(s)) => Movd::emit(self, d, s),
+            (DWordPointer(d), Xmm(s)) => Movd::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Movd {
+    dst: Xmm, src: Reg32 => (RM) Prefix(0x66), Op(0x0f), Op(0x6e), ModRm;
+    dst: Reg32, src: Xmm => (MR) Prefix(0x66), Op(0x0f), Op(0x7e), ModRm;
+    <P: Mem> dst: Xmm, src: DWord<P> => (RM) Prefix(0x66), Op(0x0f), Op(0x6e), ModRm;
+    <P: Mem> dst: DWord<P>, src: Xmm => (MR) Prefix(0x66), Op(0x0f), Op(0x7e), ModRm;
+}}
+
+
+pub trait Movq<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Movq<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Xmm(d), Xmm(s)) => Movq::emit(self, d, s),
+            (Xmm(d), Reg64(s)) => Movq::emit(self, d, s),
+            (Xmm(d), QWordPointer(s)) => Movq::emit(self, d, s),
+            (Reg64(d), Xmm(s)) => Movq::emit(self, d, s),
+            (QWordPointer(d), Xmm(s)) => Movq::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Movq {
+    dst: Xmm, src: Xmm => (RM) Prefix(0xf3), Op(0x0f), Op(0x7e), ModRm;
+    dst: Xmm, src: Reg64 => (RM) Prefix(0x66), RexW, Op(0x0f), Op(0x6e), ModRm;
+    dst: Reg64, src: Xmm => (MR) Prefix(0x66), RexW, Op(0x0f), Op(0x7e), ModRm;
+    <P: Mem> dst: Xmm, src: QWord<P> => (RM) Prefix(0xf3), Op(0x0f), Op(0x7e), ModRm;
+    <P: Mem> dst: QWord<P>, src: Xmm => (MR) Prefix(0x66), Op(0x0f), Op(0xd6), ModRm;
+}}
+
+
+macro_rules! sse_shift_op {
+    ($( ($Op:ident, $op:expr, $op_imm:expr, $index:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Imm8(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), XmmWordPointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, imm: u8 => (MI) Prefix(0x66), Op(0x0f), Op($op_imm), ModRmIndex($index), Imm8;
+            dst: Xmm, src: Xmm => (RM) Prefix(0x66), Op(0x0f), Op($op), ModRm;
+            <P: Mem> dst: Xmm, src: XmmWord<P> => (RM) Prefix(0x66), Op(0x0f), Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+sse_shift_op! {
+    (Psllw, 0xf1, 0x71, 6),
+    (Pslld, 0xf2, 0x72, 6),
+    (Psllq, 0xf3, 0x73, 6),
+    (Psrlw, 0xd1, 0x71, 2),
+    (Psrld, 0xd2, 0x72, 2),
+    (Psrlq, 0xd3, 0x73, 2)
+}
+
+
+pub trait Pmovmskb<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Pmovmskb<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Reg32(d), Xmm(s)) => Pmovmskb::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Pmovmskb {
+    dst: Reg32, src: Xmm => (RM) Prefix(0x66), Op(0x0f), Op(0xd7), ModRm;
+}}
+
+
+pub trait Pshufd<D, S, I>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S, imm: I) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Pshufd<Operand, Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand, imm: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src, imm) {
+            (Xmm(d), Xmm(s), Imm8(i)) => Pshufd::emit(self, d, s, i),
+            (Xmm(d), XmmWordPointer(s), Imm8(i)) => Pshufd::emit(self, d, s, i),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Pshufd {
+    dst: Xmm, src: Xmm, imm: u8 => (RMI) Prefix(0x66), Op(0x0f), Op(0x70), ModRm, Imm8;
+    <P: Mem> dst: Xmm, src: XmmWord<P>, imm: u8 => (RMI) Prefix(0x66), Op(0x0f), Op(0x70), ModRm, Imm8;
+}}
+
+
+macro_rules! avx_op {
+    ($( ($Op:ident, $vex:expr, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S1, S2>: EmitSlice {
+            fn emit(&mut self, dst: D, src1: S1, src2: S2) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src1: Operand, src2: Operand)
+                -> Result<(), Error<Self::Error>>
+            {
+                use operand::Operand::*;
+                match (dst, src1, src2) {
+                    (Xmm(d), Xmm(s1), Xmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Xmm(d), Xmm(s1), XmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (Ymm(d), Ymm(s1), Ymm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Ymm(d), Ymm(s1), YmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src1: Xmm, src2: Xmm => (RVM) $vex, Op($op), ModRm;
+            <P: Mem> dst: Xmm, src1: Xmm, src2: XmmWord<P> => (RVM) $vex, Op($op), ModRm;
+            dst: Ymm, src1: Ymm, src2: Ymm => (RVM) $vex, Op($op), ModRm;
+            <P: Mem> dst: Ymm, src1: Ymm, src2: YmmWord<P> => (RVM) $vex, Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+avx_op! {
+    (Vpxor,       Vex(0x66, 0x0f,   0), 0xef),
+    (Vpcmpeqb,    Vex(0x66, 0x0f,   0), 0x74)
+}
+
+
+macro_rules! avx512_operand_impl {
+    ($Op:ident, $ElemBroadcast:ident) => {
+        impl<W> $Op<Operand, Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src1: Operand, src2: Operand)
+                -> Result<(), Error<Self::Error>>
+            {
+                use operand::Operand::*;
+                match (dst, src1, src2) {
+                    (Xmm(d), Xmm(s1), Xmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Xmm(d), Xmm(s1), XmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (Xmm(d), Xmm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    (Ymm(d), Ymm(s1), Ymm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Ymm(d), Ymm(s1), YmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (Ymm(d), Ymm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    (Zmm(d), Zmm(s1), Zmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Zmm(d), Zmm(s1), ZmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (Zmm(d), Zmm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedXmm(d), Xmm(s1), Xmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedXmm(d), Xmm(s1), XmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedXmm(d), Xmm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedYmm(d), Ymm(s1), Ymm(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedYmm(d), Ymm(s1), YmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedYmm(d), Ymm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedZmm(d), Zmm(s1), Zmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedZmm(d), Zmm(s1), ZmmWordPointer(s2)) => $Op::emit(self, d, s1, s2),
+                    (MaskedZmm(d), Zmm(s1), $ElemBroadcast(s2)) => $Op::emit(self, d, s1, s2),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+    };
+}
+
+/// EVEX forms that have no VEX equivalent: 512-bit vectors, masking and
+/// embedded broadcast.
+macro_rules! avx512_forms {
+    ($Op:ident, $evex:expr, $op:expr, $Elem:ident) => {
+        op! { $Op {
+            dst: Zmm, src1: Zmm, src2: Zmm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Zmm, src1: Zmm, src2: ZmmWord<P> => (RVM) $evex, Op($op), ModRm;
+            dst: Masked<Xmm>, src1: Xmm, src2: Xmm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Xmm>, src1: Xmm, src2: XmmWord<P> => (RVM) $evex, Op($op), ModRm;
+            dst: Masked<Ymm>, src1: Ymm, src2: Ymm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Ymm>, src1: Ymm, src2: YmmWord<P> => (RVM) $evex, Op($op), ModRm;
+            dst: Masked<Zmm>, src1: Zmm, src2: Zmm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Zmm>, src1: Zmm, src2: ZmmWord<P> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Xmm, src1: Xmm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Ymm, src1: Ymm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Zmm, src1: Zmm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Xmm>, src1: Xmm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Ymm>, src1: Ymm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Masked<Zmm>, src1: Zmm, src2: Broadcast<$Elem<P>> => (RVM) $evex, Op($op), ModRm;
+        }}
+    };
+}
+
+/// Like `avx_op!`, but switches to the EVEX form when a register above 15
+/// is used and adds the AVX-512 forms.
+macro_rules! avx_evex_op {
+    ($( ($Op:ident, $vex:expr, $evex:expr, $Elem:ident, $ElemBroadcast:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S1, S2>: EmitSlice {
+            fn emit(&mut self, dst: D, src1: S1, src2: S2) -> Result<(), Error<Self::Error>>;
+        }
+
+        avx512_operand_impl!($Op, $ElemBroadcast);
+
+        op! { $Op {
+            dst: Xmm, src1: Xmm, src2: Xmm => if (dst.needs_evex() || src1.needs_evex() || src2.needs_evex()) {
+                (RVM) $evex, Op($op), ModRm
+            } else {
+                (RVM) $vex, Op($op), ModRm
+            };
+            <P: Mem> dst: Xmm, src1: Xmm, src2: XmmWord<P> => if (dst.needs_evex() || src1.needs_evex()) {
+                (RVM) $evex, Op($op), ModRm
+            } else {
+                (RVM) $vex, Op($op), ModRm
+            };
+            dst: Ymm, src1: Ymm, src2: Ymm => if (dst.needs_evex() || src1.needs_evex() || src2.needs_evex()) {
+                (RVM) $evex, Op($op), ModRm
+            } else {
+                (RVM) $vex, Op($op), ModRm
+            };
+            <P: Mem> dst: Ymm, src1: Ymm, src2: YmmWord<P> => if (dst.needs_evex() || src1.needs_evex()) {
+                (RVM) $evex, Op($op), ModRm
+            } else {
+                (RVM) $vex, Op($op), ModRm
+            };
+        }}
+
+        avx512_forms!($Op, $evex, $op, $Elem);
+        )*
+    };
+}
+
+avx_evex_op! {
+    (Vaddps,      Vex(0,    0x0f,   0), Evex(0,    0x0f,   0), DWord, DWordBroadcast, 0x58),
+    (Vaddpd,      Vex(0x66, 0x0f,   0), Evex(0x66, 0x0f,   1), QWord, QWordBroadcast, 0x58),
+    (Vmulps,      Vex(0,    0x0f,   0), Evex(0,    0x0f,   0), DWord, DWordBroadcast, 0x59),
+    (Vmulpd,      Vex(0x66, 0x0f,   0), Evex(0x66, 0x0f,   1), QWord, QWordBroadcast, 0x59),
+    (Vfmadd132ps, Vex(0x66, 0x0f38, 0), Evex(0x66, 0x0f38, 0), DWord, DWordBroadcast, 0x98),
+    (Vfmadd213ps, Vex(0x66, 0x0f38, 0), Evex(0x66, 0x0f38, 0), DWord, DWordBroadcast, 0xa8),
+    (Vfmadd231ps, Vex(0x66, 0x0f38, 0), Evex(0x66, 0x0f38, 0), DWord, DWordBroadcast, 0xb8),
+    (Vfmadd132pd, Vex(0x66, 0x0f38, 1), Evex(0x66, 0x0f38, 1), QWord, QWordBroadcast, 0x98),
+    (Vfmadd213pd, Vex(0x66, 0x0f38, 1), Evex(0x66, 0x0f38, 1), QWord, QWordBroadcast, 0xa8),
+    (Vfmadd231pd, Vex(0x66, 0x0f38, 1), Evex(0x66, 0x0f38, 1), QWord, QWordBroadcast, 0xb8),
+    (Vpaddd,      Vex(0x66, 0x0f,   0), Evex(0x66, 0x0f,   0), DWord, DWordBroadcast, 0xfe),
+    (Vpaddq,      Vex(0x66, 0x0f,   0), Evex(0x66, 0x0f,   1), QWord, QWordBroadcast, 0xd4)
+}
+
+
+macro_rules! avx_scalar_op {
+    ($( ($Op:ident, $vex:expr, $Size:ident, $SizePointer:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S1, S2>: EmitSlice {
+            fn emit(&mut self, dst: D, src1: S1, src2: S2) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src1: Operand, src2: Operand)
+                -> Result<(), Error<Self::Error>>
+            {
+                use operand::Operand::*;
+                match (dst, src1, src2) {
+                    (Xmm(d), Xmm(s1), Xmm(s2)) => $Op::emit(self, d, s1, s2),
+                    (Xmm(d), Xmm(s1), $SizePointer(s2)) => $Op::emit(self, d, s1, s2),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src1: Xmm, src2: Xmm => (RVM) $vex, Op($op), ModRm;
+            <P: Mem> dst: Xmm, src1: Xmm, src2: $Size<P> => (RVM) $vex, Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+avx_scalar_op! {
+    (Vfmadd132ss, Vex(0x66, 0x0f38, 0), DWord, DWordPointer, 0x99),
+    (Vfmadd213ss, Vex(0x66, 0x0f38, 0), DWord, DWordPointer, 0xa9),
+    (Vfmadd231ss, Vex(0x66, 0x0f38, 0), DWord, DWordPointer, 0xb9),
+    (Vfmadd132sd, Vex(0x66, 0x0f38, 1), QWord, QWordPointer, 0x99),
+    (Vfmadd213sd, Vex(0x66, 0x0f38, 1), QWord, QWordPointer, 0xa9),
+    (Vfmadd231sd, Vex(0x66, 0x0f38, 1), QWord, QWordPointer, 0xb9)
+}
+
+
+pub trait Vpmovmskb<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Vpmovmskb<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (Reg32(d), Xmm(s)) => Vpmovmskb::emit(self, d, s),
+            (Reg32(d), Ymm(s)) => Vpmovmskb::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Vpmovmskb {
+    dst: Reg32, src: Xmm => (RM) Vex(0x66, 0x0f, 0), Op(0xd7), ModRm;
+    dst: Reg32, src: Ymm => (RM) Vex(0x66, 0x0f, 0), Op(0xd7), ModRm;
+}}
+
+
+macro_rules! vpbroadcast_op {
+    ($( ($Op:ident, $Size:ident, $SizePointer:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), $SizePointer(s)) => $Op::emit(self, d, s),
+                    (Ymm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Ymm(d), $SizePointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src: Xmm => (RM) Vex(0x66, 0x0f38, 0), Op($op), ModRm;
+            <P: Mem> dst: Xmm, src: $Size<P> => (RM) Vex(0x66, 0x0f38, 0), Op($op), ModRm;
+            dst: Ymm, src: Xmm => (RM) Vex(0x66, 0x0f38, 0), Op($op), ModRm;
+            <P: Mem> dst: Ymm, src: $Size<P> => (RM) Vex(0x66, 0x0f38, 0), Op($op), ModRm;
+        }}
+        )*
+    };
+}
+
+vpbroadcast_op! {
+    (Vpbroadcastb, Byte, BytePointer, 0x78),
+    (Vpbroadcastw, Word, WordPointer, 0x79),
+    (Vpbroadcastd, DWord, DWordPointer, 0x58),
+    (Vpbroadcastq, QWord, QWordPointer, 0x59)
+}
+
+
+pub trait Vpermd<D, S1, S2>: EmitSlice {
+    fn emit(&mut self, dst: D, src1: S1, src2: S2) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Vpermd<Operand, Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src1: Operand, src2: Operand)
+        -> Result<(), Error<Self::Error>>
+    {
+        use operand::Operand::*;
+        match (dst, src1, src2) {
+            (Ymm(d), Ymm(s1), Ymm(s2)) => Vpermd::emit(self, d, s1, s2),
+            (Ymm(d), Ymm(s1), YmmWordPointer(s2)) => Vpermd::emit(self, d, s1, s2),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Vpermd {
+    dst: Ymm, src1: Ymm, src2: Ymm => (RVM) Vex(0x66, 0x0f38, 0), Op(0x36), ModRm;
+    <P: Mem> dst: Ymm, src1: Ymm, src2: YmmWord<P> => (RVM) Vex(0x66, 0x0f38, 0), Op(0x36), ModRm;
+}}
+
+
+macro_rules! vgather_op {
+    ($( ($Op:ident, $Elem:ident, $w:expr, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S, M>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S, mask: M) -> Result<(), Error<Self::Error>>;
+        }
+
+        op! { $Op {
+            <P: Vsib<Xmm>> dst: Xmm, src: $Elem<P>, mask: Xmm => (RMV) Vex(0x66, 0x0f38, $w), Op($op), ModRm;
+            <P: Vsib<Ymm>> dst: Ymm, src: $Elem<P>, mask: Ymm => (RMV) Vex(0x66, 0x0f38, $w), Op($op), ModRm;
+        }}
+        )*
+    }
+}
+
+vgather_op! {
+    (Vpgatherdd, DWord, 0, 0x90),
+    (Vpgatherqq, QWord, 1, 0x91),
+    (Vgatherdps, DWord, 0, 0x92)
+}
+
+
+macro_rules! vscatter_op {
+    ($( ($Op:ident, $Elem:ident, $w:expr, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        op! { $Op {
+            <P: Vsib<Zmm>> dst: Masked<$Elem<P>>, src: Zmm => (MR) Evex(0x66, 0x0f38, $w), Op($op), ModRm;
+        }}
+        )*
+    }
+}
+
+vscatter_op! {
+    (Vpscatterdd, DWord, 0, 0xa0),
+    (Vpscatterqq, QWord, 1, 0xa1)
+}
+
+pub trait Vzeroupper: EmitSlice {
+    fn emit(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+op! { Vzeroupper {
+    => (None) Vex(0, 0x0f, 0), Op(0x77);
+}}
+
+
+macro_rules! avx512_op {
+    ($( ($Op:ident, $evex:expr, $Elem:ident, $ElemBroadcast:ident, $op:expr) ),*) => {
+        $(
+        pub trait $Op<D, S1, S2>: EmitSlice {
+            fn emit(&mut self, dst: D, src1: S1, src2: S2) -> Result<(), Error<Self::Error>>;
+        }
+
+        avx512_operand_impl!($Op, $ElemBroadcast);
+
+        op! { $Op {
+            dst: Xmm, src1: Xmm, src2: Xmm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Xmm, src1: Xmm, src2: XmmWord<P> => (RVM) $evex, Op($op), ModRm;
+            dst: Ymm, src1: Ymm, src2: Ymm => (RVM) $evex, Op($op), ModRm;
+            <P: Mem> dst: Ymm, src1: Ymm, src2: YmmWord<P> => (RVM) $evex, Op($op), ModRm;
+        }}
+
+        avx512_forms!($Op, $evex, $op, $Elem);
+        )*
+    };
+}
+
+avx512_op! {
+    (Vpxord, Evex(0x66, 0x0f, 0), DWord, DWordBroadcast, 0xef),
+    (Vpxorq, Evex(0x66, 0x0f, 1), QWord, QWordBroadcast, 0xef),
+    (Vpandd, Evex(0x66, 0x0f, 0), DWord, DWordBroadcast, 0xdb),
+    (Vpandq, Evex(0x66, 0x0f, 1), QWord, QWordBroadcast, 0xdb)
+}
+
+
+macro_rules! avx512_move_op {
+    ($( ($Op:ident, $evex:expr, $load:expr, $store:expr) ),*) => {
+        $(
+        pub trait $Op<D, S>: EmitSlice {
+            fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+        }
+
+        impl<W> $Op<Operand, Operand> for W where W: EmitSlice {
+            fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+                use operand::Operand::*;
+                match (dst, src) {
+                    (Xmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Xmm(d), XmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (XmmWordPointer(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (Ymm(d), Ymm(s)) => $Op::emit(self, d, s),
+                    (Ymm(d), YmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (YmmWordPointer(d), Ymm(s)) => $Op::emit(self, d, s),
+                    (Zmm(d), Zmm(s)) => $Op::emit(self, d, s),
+                    (Zmm(d), ZmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (ZmmWordPointer(d), Zmm(s)) => $Op::emit(self, d, s),
+                    (MaskedXmm(d), Xmm(s)) => $Op::emit(self, d, s),
+                    (MaskedXmm(d), XmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (MaskedYmm(d), Ymm(s)) => $Op::emit(self, d, s),
+                    (MaskedYmm(d), YmmWordPointer(s)) => $Op::emit(self, d, s),
+                    (MaskedZmm(d), Zmm(s)) => $Op::emit(self, d, s),
+                    (MaskedZmm(d), ZmmWordPointer(s)) => $Op::emit(self, d, s),
+                    _ => Err(Error::InvalidOperands),
+                }
+            }
+        }
+
+        op! { $Op {
+            dst: Xmm, src: Xmm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Xmm, src: XmmWord<P> => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: XmmWord<P>, src: Xmm => (MR) $evex, Op($store), ModRm;
+            dst: Ymm, src: Ymm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Ymm, src: YmmWord<P> => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: YmmWord<P>, src: Ymm => (MR) $evex, Op($store), ModRm;
+            dst: Zmm, src: Zmm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Zmm, src: ZmmWord<P> => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: ZmmWord<P>, src: Zmm => (MR) $evex, Op($store), ModRm;
+            dst: Masked<Xmm>, src: Xmm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Masked<Xmm>, src: XmmWord<P> => (RM) $evex, Op($load), ModRm;
+            dst: Masked<Ymm>, src: Ymm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Masked<Ymm>, src: YmmWord<P> => (RM) $evex, Op($load), ModRm;
+            dst: Masked<Zmm>, src: Zmm => (RM) $evex, Op($load), ModRm;
+            <P: Mem> dst: Masked<Zmm>, src: ZmmWord<P> => (RM) $evex, Op($load), ModRm;
+        }}
+        )*
+    };
+}
+
+avx512_move_op! {
+    (Vmovdqu32, Evex(0xf3, 0x0f, 0), 0x6f, 0x7f),
+    (Vmovdqu64, Evex(0xf3, 0x0f, 1), 0x6f, 0x7f)
+}
+
+
+pub trait Kmovw<D, S>: EmitSlice {
+    fn emit(&mut self, dst: D, src: S) -> Result<(), Error<Self::Error>>;
+}
+
+impl<W> Kmovw<Operand, Operand> for W where W: EmitSlice {
+    fn emit(&mut self, dst: Operand, src: Operand) -> Result<(), Error<Self::Error>> {
+        use operand::Operand::*;
+        match (dst, src) {
+            (K(d), K(s)) => Kmovw::emit(self, d, s),
+            (K(d), Reg32(s)) => Kmovw::emit(self, d, s),
+            (Reg32(d), K(s)) => Kmovw::emit(self, d, s),
+            _ => Err(Error::InvalidOperands),
+        }
+    }
+}
+
+op! { Kmovw {
+    dst: K, src: K => (RM) Vex(0, 0x0f, 0), Op(0x90), ModRm;
+    dst: K, src: Reg32 => (RM) Vex(0, 0x0f, 0), Op(0x92), ModRm;
+    dst: Reg32, src: K => (RM) Vex(0, 0x0f, 0), Op(0x93), ModRm;
 }}
@@ -1,10 +1,15 @@
 use std::ops::{Deref, DerefMut};
 
 use arrayvec::ArrayVec;
+use mitte_core::EmitSlice;
+
+use relax;
 
 
 pub struct Buffer {
     buf: ArrayVec<u8, 32>,
+    /// Whether the instruction has a branch offset or a RIP-relative operand.
+    pc_relative: bool,
 }
 
 impl Buffer {
@@ -12,6 +17,24 @@ impl Buffer {
     pub fn new() -> Buffer {
         Buffer {
             buf: ArrayVec::new(),
+            pc_relative: false,
+        }
+    }
+
+    #[inline]
+    pub fn set_pc_relative(&mut self) {
+        self.pc_relative = true;
+    }
+
+    /// Emits the instruction, marked as PC-relative if it is one.
+    #[inline]
+    pub fn emit<E>(&self, emitter: &mut E) -> Result<(), E::Error>
+        where E: EmitSlice
+    {
+        if self.pc_relative {
+            relax::emit_pc_relative(|| emitter.emit_slice(self))
+        } else {
+            emitter.emit_slice(self)
         }
     }
 
@@ -20,6 +43,11 @@ impl Buffer {
         self.buf.push(value);
     }
 
+    #[inline]
+    pub fn write_slice(&mut self, slice: &[u8]) {
+        self.buf.try_extend_from_slice(slice).unwrap();
+    }
+
     #[inline]
     pub fn write_u16(&mut self, value: u16) {
         self.buf.try_extend_from_slice(&value.to_le_bytes()).unwrap();
@@ -1,9 +1,12 @@
+use std::convert::{Infallible, TryFrom};
 use std::fmt;
 
+use mitte_core::EmitSlice;
+
 use buffer::Buffer;
-use reg::Reg64;
+use reg::{Reg64, Rip, Xmm, Ymm, Zmm, Masked};
 use ptr::{Scale, Scaled};
-use ptr::{Ptr, Pointer};
+use ptr::{Ptr, Pointer, Seg};
 use error::Error;
 
 
@@ -17,12 +20,53 @@ impl<E> From<Error<NoError>> for Error<E>
             Error::InvalidOperands => Error::InvalidOperands,
             Error::RexIncompatibleRegister(reg) => Error::RexIncompatibleRegister(reg),
             Error::InvalidIndexRegister(reg) => Error::InvalidIndexRegister(reg),
+            Error::BranchOutOfRange { offset } => Error::BranchOutOfRange { offset },
+            Error::Fixup(error) => Error::Fixup(error),
+            Error::LabelAlreadyBound(label) => Error::LabelAlreadyBound(label),
+            Error::UnboundLabel(label) => Error::UnboundLabel(label),
+            Error::InvalidLabel(label) => Error::InvalidLabel(label),
+            Error::PcRelative { pos } => Error::PcRelative { pos },
+            Error::EvexOnlyRegister(reg) => Error::EvexOnlyRegister(reg),
             Error::Custom(e) => match e {},
         }
     }
 }
 
 
+/// An emitter that only counts the bytes it is given.
+pub struct Measure(pub usize);
+
+impl EmitSlice for Measure {
+    type Error = Infallible;
+
+    #[inline]
+    fn emit_slice(&mut self, slice: &[u8]) -> Result<(), Infallible> {
+        self.0 += slice.len();
+        Ok(())
+    }
+}
+
+pub fn measure<F>(f: F) -> Result<usize, Error<NoError>>
+    where F: FnOnce(&mut Measure) -> Result<(), Error<Infallible>>
+{
+    let mut m = Measure(0);
+    match f(&mut m) {
+        Ok(()) => Ok(m.0),
+        Err(Error::InvalidOperands) => Err(Error::InvalidOperands),
+        Err(Error::RexIncompatibleRegister(reg)) => Err(Error::RexIncompatibleRegister(reg)),
+        Err(Error::InvalidIndexRegister(reg)) => Err(Error::InvalidIndexRegister(reg)),
+        Err(Error::BranchOutOfRange { offset }) => Err(Error::BranchOutOfRange { offset }),
+        Err(Error::Fixup(error)) => Err(Error::Fixup(error)),
+        Err(Error::LabelAlreadyBound(label)) => Err(Error::LabelAlreadyBound(label)),
+        Err(Error::UnboundLabel(label)) => Err(Error::UnboundLabel(label)),
+        Err(Error::InvalidLabel(label)) => Err(Error::InvalidLabel(label)),
+        Err(Error::PcRelative { pos }) => Err(Error::PcRelative { pos }),
+        Err(Error::EvexOnlyRegister(reg)) => Err(Error::EvexOnlyRegister(reg)),
+        Err(Error::Custom(e)) => match e {},
+    }
+}
+
+
 pub trait Register: Copy + 'static + fmt::Debug {
     /// Returns the register size in bytes.
     fn size(&self) -> usize;
@@ -30,11 +74,82 @@ pub trait Register: Copy + 'static + fmt::Debug {
     fn has_extended_index(&self) -> bool;
     fn is_encodable_with_rex(&self) -> bool;
     fn needs_rex(&self) -> bool;
+    /// Returns whether the register number is above 15 and can only be
+    /// encoded with an EVEX prefix.
+    fn needs_evex(&self) -> bool;
     fn rm(&self) -> u8;
     fn check_is_rex_compatible(&self) -> Result<(), Error<NoError>>;
 }
 
 
+/// A destination register of an EVEX instruction, optionally masked.
+pub trait EvexDst: Copy {
+    type Reg: Register;
+
+    fn reg(&self) -> Self::Reg;
+    /// Returns the z and aaa fields of the EVEX prefix.
+    fn mask(&self) -> u8;
+}
+
+impl EvexDst for Xmm {
+    type Reg = Xmm;
+
+    #[inline]
+    fn reg(&self) -> Xmm {
+        *self
+    }
+
+    #[inline]
+    fn mask(&self) -> u8 {
+        0
+    }
+}
+
+impl EvexDst for Ymm {
+    type Reg = Ymm;
+
+    #[inline]
+    fn reg(&self) -> Ymm {
+        *self
+    }
+
+    #[inline]
+    fn mask(&self) -> u8 {
+        0
+    }
+}
+
+impl EvexDst for Zmm {
+    type Reg = Zmm;
+
+    #[inline]
+    fn reg(&self) -> Zmm {
+        *self
+    }
+
+    #[inline]
+    fn mask(&self) -> u8 {
+        0
+    }
+}
+
+impl<R> EvexDst for Masked<R>
+    where R: Register
+{
+    type Reg = R;
+
+    #[inline]
+    fn reg(&self) -> R {
+        self.reg
+    }
+
+    #[inline]
+    fn mask(&self) -> u8 {
+        (self.zeroing as u8) << 7 | self.mask.rm()
+    }
+}
+
+
 pub fn rex_r<R>(r: R) -> Result<Option<u8>, Error<NoError>>
     where R: Register
 {
@@ -42,6 +157,8 @@ pub fn rex_r<R>(r: R) -> Result<Option<u8>, Error<NoError>>
         return Ok(None);
     }
 
+    r.check_is_rex_compatible()?;
+
     let rex = 0x40
         | (r.is_64bit() as u8) << 3
         | (r.has_extended_index() as u8) << 2;
@@ -56,6 +173,8 @@ pub fn rex_x<X>(x: X) -> Result<Option<u8>, Error<NoError>>
         return Ok(None);
     }
 
+    x.check_is_rex_compatible()?;
+
     let rex = 0x40
         | (x.is_64bit() as u8) << 3
         | (x.has_extended_index() as u8) << 1;
@@ -70,6 +189,8 @@ pub fn rex_b<B>(b: B) -> Result<Option<u8>, Error<NoError>>
         return Ok(None);
     }
 
+    b.check_is_rex_compatible()?;
+
     let rex = 0x40
         | (b.is_64bit() as u8) << 3
         | b.has_extended_index() as u8;
@@ -152,6 +273,162 @@ pub fn rex_rxb<R, X, B>(r: R, x: X, b: B) -> Result<Option<u8>, Error<NoError>>
 }
 
 
+/// Returns the register number that goes into the inverted VEX.vvvv field.
+#[inline]
+pub fn vex_reg<R>(r: R) -> Result<u8, Error<NoError>>
+    where R: Register
+{
+    r.check_is_rex_compatible()?;
+    Ok((r.has_extended_index() as u8) << 3 | r.rm())
+}
+
+/// Writes a 2- or 3-byte VEX prefix.
+///
+/// `rex` is the REX prefix the instruction would have had, from which the
+/// R, X and B bits are taken; they are stored inverted. `prefix` is the
+/// implied legacy prefix (0 for none) and `map` the implied escape bytes
+/// (0x0f, 0x0f38 or 0x0f3a).
+#[inline]
+pub fn write_vex(buffer: &mut Buffer, rex: Option<u8>, prefix: u8, map: u16, w: u8, vvvv: u8, l: bool) {
+    let rex = rex.unwrap_or(0);
+    let pp = match prefix {
+        0 => 0,
+        0x66 => 1,
+        0xf3 => 2,
+        0xf2 => 3,
+        _ => unreachable!(),
+    };
+    let mmmmm = match map {
+        0x0f => 1,
+        0x0f38 => 2,
+        0x0f3a => 3,
+        _ => unreachable!(),
+    };
+    debug_assert!(w < 2);
+    debug_assert!(vvvv < 16);
+
+    let last = (!vvvv & 0xf) << 3 | (l as u8) << 2 | pp;
+    if rex & 0x3 == 0 && mmmmm == 1 && w == 0 {
+        buffer.write_u8(0xc5);
+        buffer.write_u8((!rex & 0x4) << 5 | last);
+    } else {
+        buffer.write_u8(0xc4);
+        buffer.write_u8((!rex & 0x7) << 5 | mmmmm);
+        buffer.write_u8(w << 7 | last);
+    }
+}
+
+
+/// Returns the full five-bit number of a register for an EVEX prefix.
+#[inline]
+pub fn evex_reg<R>(r: R) -> u8
+    where R: Register
+{
+    (r.needs_evex() as u8) << 4 | (r.has_extended_index() as u8) << 3 | r.rm()
+}
+
+/// Returns the R', R, X and B bits of an EVEX prefix for a register operand
+/// `reg` and a register operand `rm`, which uses X as its fifth bit.
+#[inline]
+pub fn evex_rrxb<R, B>(reg: R, rm: B) -> u8
+    where R: Register, B: Register
+{
+    (reg.needs_evex() as u8) << 3
+        | (reg.has_extended_index() as u8) << 2
+        | (rm.needs_evex() as u8) << 1
+        | rm.has_extended_index() as u8
+}
+
+/// Like `evex_rrxb` for a memory operand `rm`, whose X and B bits come
+/// from its REX prefix.
+#[inline]
+pub fn evex_rrxb_mem<R, M>(reg: R, rm: &M) -> Result<u8, Error<NoError>>
+    where R: Register, M: Rex
+{
+    let rex = rm.rex()?.unwrap_or(0);
+    Ok((reg.needs_evex() as u8) << 3
+        | (reg.has_extended_index() as u8) << 2
+        | rex & 0x3)
+}
+
+/// Like `evex_rrxb_mem` for a VSIB memory operand, whose vector index
+/// may be one of the registers only EVEX can encode.
+#[inline]
+pub fn evex_rrxb_vsib<R, M>(reg: R, rm: &M) -> u8
+    where R: Register, M: VsibArgs
+{
+    (reg.needs_evex() as u8) << 3
+        | (reg.has_extended_index() as u8) << 2
+        | (rm.index().has_extended_index() as u8) << 1
+        | rm.base().has_extended_index() as u8
+}
+
+/// Returns the EVEX.vvvv field for a VSIB memory operand, where only
+/// V' is used, to extend the vector index.
+#[inline]
+pub fn evex_vsib_reg<X>(index: X) -> u8
+    where X: Register
+{
+    (index.needs_evex() as u8) << 4
+}
+
+/// Returns the EVEX.L'L field for a vector register.
+#[inline]
+pub fn evex_ll<R>(r: R) -> u8
+    where R: Register
+{
+    match r.size() {
+        16 => 0,
+        32 => 1,
+        64 => 2,
+        _ => unreachable!(),
+    }
+}
+
+/// Writes a 4-byte EVEX prefix.
+///
+/// `rrxb` holds the R', R, X and B bits, not yet inverted, `vvvv` the five-bit
+/// number of the extra register operand and `p2` the z, L'L, b and aaa fields
+/// of the last byte. `prefix` and `map` are as for `write_vex`.
+#[inline]
+pub fn write_evex(buffer: &mut Buffer, rrxb: u8, prefix: u8, map: u16, w: u8, vvvv: u8, p2: u8) {
+    let pp = match prefix {
+        0 => 0,
+        0x66 => 1,
+        0xf3 => 2,
+        0xf2 => 3,
+        _ => unreachable!(),
+    };
+    let mm = match map {
+        0x0f => 1,
+        0x0f38 => 2,
+        0x0f3a => 3,
+        _ => unreachable!(),
+    };
+    debug_assert!(rrxb < 16);
+    debug_assert!(w < 2);
+    debug_assert!(vvvv < 32);
+    debug_assert!(p2 & 0x08 == 0);
+
+    let r = (!rrxb & 0x7) << 5 | (!rrxb & 0x8) << 1;
+    buffer.write_u8(0x62);
+    buffer.write_u8(r | mm);
+    buffer.write_u8(w << 7 | (!vvvv & 0xf) << 3 | 0x4 | pp);
+    buffer.write_u8(p2 | (!vvvv & 0x10) >> 1);
+}
+
+/// Returns the EVEX compressed disp8 for `disp` if it is a multiple of the
+/// memory operand width `n` and the quotient fits in a byte.
+#[inline]
+pub fn compress_disp(disp: i32, n: i32) -> Option<i8> {
+    if disp % n == 0 {
+        i8::try_from(disp / n).ok()
+    } else {
+        None
+    }
+}
+
+
 #[inline]
 pub fn modrm(mode: u8, reg: u8, rm: u8) -> u8 {
     debug_assert!(mode < 4);
@@ -178,6 +455,14 @@ pub fn write_reg_disp(buffer: &mut Buffer, reg: u8, disp: i32) -> Result<(), Err
     Ok(())
 }
 
+#[inline]
+pub fn write_reg_rip_disp(buffer: &mut Buffer, reg: u8, disp: i32) -> Result<(), Error<NoError>> {
+    buffer.set_pc_relative();
+    buffer.write_u8(modrm(0, reg, 5));
+    buffer.write_u32(disp as u32);
+    Ok(())
+}
+
 #[inline]
 pub fn write_reg_base(buffer: &mut Buffer, reg: u8, base: Reg64) -> Result<(), Error<NoError>> {
     if base.rm() == 5 { // rbp, r13
@@ -273,6 +558,41 @@ pub fn write_reg_base_index_disp32(buffer: &mut Buffer, reg: u8, base: Reg64, in
 }
 
 
+#[inline]
+pub fn write_reg_base_vsib<X>(buffer: &mut Buffer, reg: u8, base: Reg64, index: X, scale: Scale) -> Result<(), Error<NoError>>
+    where X: Register
+{
+    if base.rm() == 5 { // rbp, r13
+        buffer.write_u8(modrm(1, reg, 4));
+        buffer.write_u8(sib(scale as u8, index.rm(), base.rm()));
+        buffer.write_u8(0);
+    } else {
+        buffer.write_u8(modrm(0, reg, 4));
+        buffer.write_u8(sib(scale as u8, index.rm(), base.rm()));
+    }
+    Ok(())
+}
+
+#[inline]
+pub fn write_reg_base_vsib_disp8<X>(buffer: &mut Buffer, reg: u8, base: Reg64, index: X, scale: Scale, disp: i8) -> Result<(), Error<NoError>>
+    where X: Register
+{
+    buffer.write_u8(modrm(1, reg, 4));
+    buffer.write_u8(sib(scale as u8, index.rm(), base.rm()));
+    buffer.write_u8(disp as u8);
+    Ok(())
+}
+
+#[inline]
+pub fn write_reg_base_vsib_disp32<X>(buffer: &mut Buffer, reg: u8, base: Reg64, index: X, scale: Scale, disp: i32) -> Result<(), Error<NoError>>
+    where X: Register
+{
+    buffer.write_u8(modrm(2, reg, 4));
+    buffer.write_u8(sib(scale as u8, index.rm(), base.rm()));
+    buffer.write_u32(disp as u32);
+    Ok(())
+}
+
 pub trait Rex {
     fn rex(&self) -> Result<Option<u8>, Error<NoError>>;
     fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>> where R: Register;
@@ -303,6 +623,18 @@ impl<D> Rex for Ptr<(), (), D> {
     }
 }
 
+impl<D> Rex for Ptr<Rip, (), D> {
+    fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
+        Ok(None)
+    }
+
+    fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>>
+        where R: Register
+    {
+        rex_r(reg)
+    }
+}
+
 impl<D> Rex for Ptr<Reg64, (), D> {
     fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
         rex_b(self.base.to_reg32())
@@ -339,12 +671,49 @@ impl<D> Rex for Ptr<Reg64, Scaled<Reg64>, D> {
     }
 }
 
+impl<D> Rex for Ptr<Reg64, Scaled<Xmm>, D> {
+    fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
+        rex_xb(self.index.0, self.base.to_reg32())
+    }
+
+    fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>>
+        where R: Register
+    {
+        rex_rxb(reg, self.index.0, self.base.to_reg32())
+    }
+}
+
+impl<D> Rex for Ptr<Reg64, Scaled<Ymm>, D> {
+    fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
+        rex_xb(self.index.0, self.base.to_reg32())
+    }
+
+    fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>>
+        where R: Register
+    {
+        rex_rxb(reg, self.index.0, self.base.to_reg32())
+    }
+}
+
+impl<D> Rex for Ptr<Reg64, Scaled<Zmm>, D> {
+    fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
+        rex_xb(self.index.0, self.base.to_reg32())
+    }
+
+    fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>>
+        where R: Register
+    {
+        rex_rxb(reg, self.index.0, self.base.to_reg32())
+    }
+}
+
 impl Rex for Pointer {
     fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
         use ptr::Pointer::*;
         match *self {
             Disp8(_) |
-            Disp32(_) => {
+            Disp32(_) |
+            RipDisp32(_) => {
                 Ok(None)
             }
             Base(base) |
@@ -371,7 +740,8 @@ impl Rex for Pointer {
         use ptr::Pointer::*;
         match *self {
             Disp8(_) |
-            Disp32(_) => {
+            Disp32(_) |
+            RipDisp32(_) => {
                 rex_r(reg)
             }
             Base(base) |
@@ -394,8 +764,35 @@ impl Rex for Pointer {
 }
 
 
+impl<P> Rex for Seg<P> where P: Rex {
+    #[inline]
+    fn rex(&self) -> Result<Option<u8>, Error<NoError>> {
+        self.1.rex()
+    }
+
+    #[inline]
+    fn rex_reg<R>(&self, reg: R) -> Result<Option<u8>, Error<NoError>>
+        where R: Register
+    {
+        self.1.rex_reg(reg)
+    }
+}
+
 pub trait Args {
     fn write(buffer: &mut Buffer, ptr: Self, reg: u8) -> Result<(), Error<NoError>>;
+
+    /// Writes the operand with an EVEX disp8*N compressed displacement,
+    /// where `n` is the width of the memory access in bytes.
+    fn write_compressed(buffer: &mut Buffer, ptr: Self, reg: u8, _n: i32) -> Result<(), Error<NoError>>
+        where Self: Sized
+    {
+        Args::write(buffer, ptr, reg)
+    }
+
+    /// Writes the segment override prefix, which goes after a lock or repeat
+    /// prefix but in front of the operand size prefix and REX.
+    #[inline]
+    fn write_segment(_buffer: &mut Buffer, _ptr: &Self) {}
 }
 
 impl Args for Ptr<(), (), i8> {
@@ -412,6 +809,13 @@ impl Args for Ptr<(), (), i32> {
     }
 }
 
+impl Args for Ptr<Rip, (), i32> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_rip_disp(buffer, reg, p.disp)
+    }
+}
+
 impl Args for Ptr<Reg64, (), ()> {
     #[inline]
     fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
@@ -424,6 +828,14 @@ impl Args for Ptr<Reg64, (), i8> {
     fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
         write_reg_base_disp8(buffer, reg, p.base, p.disp)
     }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp as i32, n) {
+            Some(disp) => write_reg_base_disp8(buffer, reg, p.base, disp),
+            None => write_reg_base_disp32(buffer, reg, p.base, p.disp as i32),
+        }
+    }
 }
 
 impl Args for Ptr<Reg64, (), i32> {
@@ -431,6 +843,14 @@ impl Args for Ptr<Reg64, (), i32> {
     fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
         write_reg_base_disp32(buffer, reg, p.base, p.disp)
     }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp, n) {
+            Some(disp) => write_reg_base_disp8(buffer, reg, p.base, disp),
+            None => write_reg_base_disp32(buffer, reg, p.base, p.disp),
+        }
+    }
 }
 
 impl Args for Ptr<(), Scaled<Reg64>, ()> {
@@ -466,6 +886,14 @@ impl Args for Ptr<Reg64, Scaled<Reg64>, i8> {
     fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
         write_reg_base_index_disp8(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
     }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp as i32, n) {
+            Some(disp) => write_reg_base_index_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_index_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp as i32),
+        }
+    }
 }
 
 impl Args for Ptr<Reg64, Scaled<Reg64>, i32> {
@@ -473,6 +901,125 @@ impl Args for Ptr<Reg64, Scaled<Reg64>, i32> {
     fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
         write_reg_base_index_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
     }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp, n) {
+            Some(disp) => write_reg_base_index_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_index_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Xmm>, ()> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib(buffer, reg, p.base, p.index.0, p.index.1)
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Xmm>, i8> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp as i32, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp as i32),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Xmm>, i32> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Ymm>, ()> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib(buffer, reg, p.base, p.index.0, p.index.1)
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Ymm>, i8> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp as i32, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp as i32),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Ymm>, i32> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Zmm>, ()> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib(buffer, reg, p.base, p.index.0, p.index.1)
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Zmm>, i8> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp as i32, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp as i32),
+        }
+    }
+}
+
+impl Args for Ptr<Reg64, Scaled<Zmm>, i32> {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        match compress_disp(p.disp, n) {
+            Some(disp) => write_reg_base_vsib_disp8(buffer, reg, p.base, p.index.0, p.index.1, disp),
+            None => write_reg_base_vsib_disp32(buffer, reg, p.base, p.index.0, p.index.1, p.disp),
+        }
+    }
 }
 
 impl Args for Pointer {
@@ -482,6 +1029,7 @@ impl Args for Pointer {
         match p {
             Disp8(disp) => write_reg_disp(buffer, reg, disp as i32),
             Disp32(disp) => write_reg_disp(buffer, reg, disp),
+            RipDisp32(disp) => write_reg_rip_disp(buffer, reg, disp),
             Base(base) => write_reg_base(buffer, reg, base),
             BaseDisp8(base, disp) => write_reg_base_disp8(buffer, reg, base, disp),
             BaseDisp32(base, disp) => write_reg_base_disp32(buffer, reg, base, disp),
@@ -503,4 +1051,92 @@ impl Args for Pointer {
             }
         }
     }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        use ptr::Pointer::*;
+        match p {
+            BaseDisp8(base, disp) => {
+                Args::write_compressed(buffer, Ptr::new(base, (), disp), reg, n)
+            }
+            BaseDisp32(base, disp) => {
+                Args::write_compressed(buffer, Ptr::new(base, (), disp), reg, n)
+            }
+            BaseIndexDisp8(base, index, disp) => {
+                Args::write_compressed(buffer, Ptr::new(base, index, disp), reg, n)
+            }
+            BaseIndexDisp32(base, index, disp) => {
+                Args::write_compressed(buffer, Ptr::new(base, index, disp), reg, n)
+            }
+            _ => Args::write(buffer, p, reg),
+        }
+    }
+}
+
+
+impl<P> Args for Seg<P> where P: Args {
+    #[inline]
+    fn write(buffer: &mut Buffer, p: Self, reg: u8) -> Result<(), Error<NoError>> {
+        Args::write(buffer, p.1, reg)
+    }
+
+    #[inline]
+    fn write_compressed(buffer: &mut Buffer, p: Self, reg: u8, n: i32) -> Result<(), Error<NoError>> {
+        Args::write_compressed(buffer, p.1, reg, n)
+    }
+
+    #[inline]
+    fn write_segment(buffer: &mut Buffer, p: &Self) {
+        buffer.write_u8(p.0.prefix());
+    }
+}
+
+/// The base and vector index registers of a VSIB memory operand.
+pub trait VsibArgs {
+    type Index: Register;
+
+    fn base(&self) -> Reg64;
+    fn index(&self) -> Self::Index;
+}
+
+impl<D> VsibArgs for Ptr<Reg64, Scaled<Xmm>, D> {
+    type Index = Xmm;
+
+    #[inline]
+    fn base(&self) -> Reg64 {
+        self.base
+    }
+
+    #[inline]
+    fn index(&self) -> Xmm {
+        self.index.0
+    }
+}
+
+impl<D> VsibArgs for Ptr<Reg64, Scaled<Ymm>, D> {
+    type Index = Ymm;
+
+    #[inline]
+    fn base(&self) -> Reg64 {
+        self.base
+    }
+
+    #[inline]
+    fn index(&self) -> Ymm {
+        self.index.0
+    }
+}
+
+impl<D> VsibArgs for Ptr<Reg64, Scaled<Zmm>, D> {
+    type Index = Zmm;
+
+    #[inline]
+    fn base(&self) -> Reg64 {
+        self.base
+    }
+
+    #[inline]
+    fn index(&self) -> Zmm {
+        self.index.0
+    }
 }
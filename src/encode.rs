@@ -1,8 +1,9 @@
 use mitte_core::EmitSlice;
 
 use common::*;
-use reg::{Reg8, Reg16, Reg32, Reg64};
-use ptr::{Mem, Byte, Word, DWord, QWord};
+use reg::{Reg8, Reg16, Reg32, Reg64, Xmm, Ymm, Zmm, K, Masked, Segment};
+use ptr::{Mem, Vsib, Byte, Word, DWord, QWord, XmmWord, YmmWord, ZmmWord, Broadcast, Lock};
+use amd64::string::{Rep, Repe, Repne};
 use error::Error;
 use buffer::Buffer;
 
@@ -16,7 +17,17 @@ pub struct M1;
 pub struct MI;
 pub struct MC;
 pub struct MR;
+#[allow(clippy::upper_case_acronyms)]
+pub struct MRI;
+#[allow(clippy::upper_case_acronyms)]
+pub struct MRC;
 pub struct RM;
+#[allow(clippy::upper_case_acronyms)]
+pub struct RMI;
+#[allow(clippy::upper_case_acronyms)]
+pub struct RVM;
+#[allow(clippy::upper_case_acronyms)]
+pub struct RMV;
 pub struct OI;
 pub struct XchgSrc;
 pub struct XchgDst;
@@ -25,6 +36,12 @@ pub struct Prefix(pub u8);
 
 pub struct RexW;
 
+/// VEX prefix with the implied legacy prefix, escape bytes and VEX.W.
+pub struct Vex(pub u8, pub u16, pub u8);
+
+/// EVEX prefix with the implied legacy prefix, escape bytes and EVEX.W.
+pub struct Evex(pub u8, pub u16, pub u8);
+
 pub struct Op(pub u8);
 pub struct OpPlusReg(pub u8);
 
@@ -49,7 +66,7 @@ impl Encode<None, ()> for Op {
         let Op(op) = this;
         let mut buffer = Buffer::new();
         buffer.write_u8(op);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -62,7 +79,47 @@ impl Encode<None, ()> for (Op, Op) {
         let mut buffer = Buffer::new();
         buffer.write_u8(op1);
         buffer.write_u8(op2);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<None, ()> for (Op, Op, Op) {
+    fn encode<E>(emitter: &mut E, _: (), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), Op(op3)) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(op3);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<None, ()> for (Prefix, Op) {
+    fn encode<E>(emitter: &mut E, _: (), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op)) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        buffer.write_u8(op);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<None, ()> for (RexW, Op) {
+    fn encode<E>(emitter: &mut E, _: (), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op)) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(0x48);
+        buffer.write_u8(op);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -75,7 +132,7 @@ impl Encode<I, u8> for (Op, Imm8) {
         let mut buffer = Buffer::new();
         buffer.write_u8(op);
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -86,9 +143,10 @@ impl Encode<D, i8> for (Op, Imm8) {
     {
         let (Op(op), Imm8) = this;
         let mut buffer = Buffer::new();
+        buffer.set_pc_relative();
         buffer.write_u8(op);
         buffer.write_u8(imm as u8);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -102,7 +160,35 @@ impl Encode<I, u16> for (Prefix, Op, Imm16) {
         buffer.write_u8(prefix);
         buffer.write_u8(op);
         buffer.write_u16(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<I, u16> for (Op, Imm16) {
+    fn encode<E>(emitter: &mut E, imm: u16, this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), Imm16) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(op);
+        buffer.write_u16(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<I, (u16, u8)> for (Op, Imm16, Imm8) {
+    fn encode<E>(emitter: &mut E, arg: (u16, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (imm16, imm8) = arg;
+        let (Op(op), Imm16, Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(op);
+        buffer.write_u16(imm16);
+        buffer.write_u8(imm8);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -115,7 +201,7 @@ impl Encode<I, u32> for (Op, Imm32) {
         let mut buffer = Buffer::new();
         buffer.write_u8(op);
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -126,9 +212,10 @@ impl Encode<D, i32> for (Op, Imm32) {
     {
         let (Op(op), Imm32) = this;
         let mut buffer = Buffer::new();
+        buffer.set_pc_relative();
         buffer.write_u8(op);
         buffer.write_u32(imm as u32);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -139,14 +226,105 @@ impl Encode<D, i32> for (Op, Op, Imm32) {
     {
         let (Op(op1), Op(op2), Imm32) = this;
         let mut buffer = Buffer::new();
+        buffer.set_pc_relative();
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u32(imm as u32);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
+/// An emitter that puts a legacy prefix in front of the instruction it is
+/// given, so that nothing is emitted if encoding fails.
+struct Prefixed<'a, E: 'a>(u8, &'a mut E);
+
+impl<'a, E> EmitSlice for Prefixed<'a, E>
+    where E: EmitSlice
+{
+    type Error = E::Error;
+
+    #[inline]
+    fn emit_slice(&mut self, slice: &[u8]) -> Result<(), E::Error> {
+        let mut buffer = Buffer::new();
+        buffer.write_u8(self.0);
+        buffer.write_slice(slice);
+        self.1.emit_slice(&buffer)
+    }
+}
+
+/// An emitter that puts a segment override prefix in front of each
+/// instruction it is given, for `Operand` memory operands, which can't carry
+/// a segment like `Seg` does. Only instructions with a memory operand may be
+/// emitted through it.
+pub struct Segmented<'a, E: 'a>(pub Segment, pub &'a mut E);
+
+impl<'a, E> EmitSlice for Segmented<'a, E>
+    where E: EmitSlice
+{
+    type Error = E::Error;
+
+    #[inline]
+    fn emit_slice(&mut self, slice: &[u8]) -> Result<(), E::Error> {
+        Prefixed(self.0.prefix(), self.1).emit_slice(slice)
+    }
+}
+
+impl<Enc, A, T> Encode<Enc, Lock<A>> for T
+    where T: Encode<Enc, A>
+{
+    fn encode<E>(emitter: &mut E, arg: Lock<A>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        T::encode(&mut Prefixed(0xf0, emitter), arg.0, this)
+    }
+}
+
+impl<Enc, A, S, T> Encode<Enc, (Lock<A>, S)> for T
+    where T: Encode<Enc, (A, S)>
+{
+    fn encode<E>(emitter: &mut E, (dst, src): (Lock<A>, S), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        T::encode(&mut Prefixed(0xf0, emitter), (dst.0, src), this)
+    }
+}
+
+impl<Enc, A, T> Encode<Enc, Rep<A>> for T
+    where T: Encode<Enc, A>
+{
+    fn encode<E>(emitter: &mut E, arg: Rep<A>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        T::encode(&mut Prefixed(0xf3, emitter), arg.0, this)
+    }
+}
+
+impl<Enc, A, T> Encode<Enc, Repe<A>> for T
+    where T: Encode<Enc, A>
+{
+    fn encode<E>(emitter: &mut E, arg: Repe<A>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        T::encode(&mut Prefixed(0xf3, emitter), arg.0, this)
+    }
+}
+
+impl<Enc, A, T> Encode<Enc, Repne<A>> for T
+    where T: Encode<Enc, A>
+{
+    fn encode<E>(emitter: &mut E, arg: Repne<A>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        T::encode(&mut Prefixed(0xf2, emitter), arg.0, this)
+    }
+}
+
 impl Encode<O, Reg8> for OpPlusReg {
     fn encode<E>(emitter: &mut E, reg: Reg8, this: Self) -> Result<(), Error<E::Error>>
         where E: EmitSlice
@@ -157,7 +335,7 @@ impl Encode<O, Reg8> for OpPlusReg {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -173,7 +351,7 @@ impl Encode<M, Reg8> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -190,7 +368,7 @@ impl Encode<M, Reg8> for (Op, Op, ModRmIndex) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -206,7 +384,7 @@ impl Encode<O, Reg16> for (Prefix, OpPlusReg) {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -223,7 +401,7 @@ impl Encode<M, Reg16> for (Prefix, Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -239,7 +417,7 @@ impl Encode<M, Reg32> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -254,7 +432,7 @@ impl Encode<O, Reg64> for OpPlusReg {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -270,7 +448,7 @@ impl Encode<M, Reg64> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -286,7 +464,7 @@ impl Encode<M, Reg64> for (RexW, Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -301,13 +479,37 @@ impl<P> Encode<M, Byte<P>> for (Op, Op, ModRmIndex)
         let (Op(op1), Op(op2), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M, Byte<P>> for (Prefix, Op, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, ptr: Byte<P>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex()? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -322,12 +524,13 @@ impl<P> Encode<M, Byte<P>> for (Op, ModRmIndex)
         let (Op(op), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex()? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -342,13 +545,14 @@ impl<P> Encode<M, Word<P>> for (Prefix, Op, ModRmIndex)
         let (Prefix(prefix), Op(op), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex()? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -363,12 +567,13 @@ impl<P> Encode<M, DWord<P>> for (Op, ModRmIndex)
         let (Op(op), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex()? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -383,12 +588,13 @@ impl<P> Encode<M, QWord<P>> for (Op, ModRmIndex)
         let (Op(op), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex()? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -403,10 +609,53 @@ impl<P> Encode<M, QWord<P>> for (RexW, Op, ModRmIndex)
         let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw()?);
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M, QWord<P>> for (Op, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, ptr: QWord<P>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M, XmmWord<P>> for (RexW, Op, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, ptr: XmmWord<P>, this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -420,7 +669,7 @@ impl Encode<I, (Reg8, u8)> for (Op, Imm8) {
         let mut buffer = Buffer::new();
         buffer.write_u8(op);
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -436,7 +685,7 @@ impl Encode<OI, (Reg8, u8)> for (OpPlusReg, Imm8) {
         }
         buffer.write_u8(op | reg.rm());
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -453,7 +702,7 @@ impl Encode<M1, (Reg8, u8)> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -470,7 +719,7 @@ impl Encode<MI, (Reg8, u8)> for (Op, ModRmIndex, Imm8) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -487,7 +736,7 @@ impl Encode<MC, (Reg8, Reg8)> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -503,7 +752,24 @@ impl Encode<MR, (Reg8, Reg8)> for (Op, ModRm) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MR, (Reg8, Reg8)> for (Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg8, Reg8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -518,12 +784,13 @@ impl<P> Encode<RM, (Reg8, Byte<P>)> for (Op, ModRm)
         let (Op(op), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -538,7 +805,7 @@ impl Encode<I, (Reg16, u16)> for (Prefix, Op, Imm16) {
         buffer.write_u8(prefix);
         buffer.write_u8(op);
         buffer.write_u16(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -556,7 +823,7 @@ impl Encode<M1, (Reg16, u8)> for (Prefix, Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -574,7 +841,26 @@ impl Encode<MI, (Reg16, u8)> for (Prefix, Op, ModRmIndex, Imm8) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MI, (Reg16, u8)> for (Prefix, Op, Op, ModRmIndex, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Reg16, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -591,7 +877,7 @@ impl Encode<OI, (Reg16, u16)> for (Prefix, OpPlusReg, Imm16) {
         }
         buffer.write_u8(op | reg.rm());
         buffer.write_u16(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -609,7 +895,7 @@ impl Encode<MI, (Reg16, u16)> for (Prefix, Op, ModRmIndex, Imm16) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u16(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -627,7 +913,7 @@ impl Encode<MC, (Reg16, Reg8)> for (Prefix, Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -645,7 +931,7 @@ impl Encode<RM, (Reg16, Reg8)> for (Prefix, Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -662,7 +948,7 @@ impl Encode<XchgSrc, (Reg16, Reg16)> for (Prefix, OpPlusReg) {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -679,7 +965,7 @@ impl Encode<XchgDst, (Reg16, Reg16)> for (Prefix, OpPlusReg) {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -696,7 +982,25 @@ impl Encode<MR, (Reg16, Reg16)> for (Prefix, Op, ModRm) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MR, (Reg16, Reg16)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg16, Reg16), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -714,7 +1018,7 @@ impl Encode<RM, (Reg16, Reg16)> for (Prefix, Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -729,6 +1033,7 @@ impl<P> Encode<RM, (Reg16, Byte<P>)> for (Prefix, Op, Op, ModRm)
         let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
@@ -736,7 +1041,7 @@ impl<P> Encode<RM, (Reg16, Byte<P>)> for (Prefix, Op, Op, ModRm)
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -751,13 +1056,14 @@ impl<P> Encode<RM, (Reg16, Word<P>)> for (Prefix, Op, ModRm)
         let (Prefix(prefix), Op(op), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -772,6 +1078,7 @@ impl<P> Encode<RM, (Reg16, Word<P>)> for (Prefix, Op, Op, ModRm)
         let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
@@ -779,7 +1086,7 @@ impl<P> Encode<RM, (Reg16, Word<P>)> for (Prefix, Op, Op, ModRm)
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -796,7 +1103,7 @@ impl Encode<M1, (Reg32, u8)> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -813,7 +1120,25 @@ impl Encode<MI, (Reg32, u8)> for (Op, ModRmIndex, Imm8) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MI, (Reg32, u8)> for (Op, Op, ModRmIndex, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Reg32, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -827,7 +1152,7 @@ impl Encode<I, (Reg32, u32)> for (Op, Imm32) {
         let mut buffer = Buffer::new();
         buffer.write_u8(op);
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -843,7 +1168,7 @@ impl Encode<OI, (Reg32, u32)> for (OpPlusReg, Imm32) {
         }
         buffer.write_u8(op | reg.rm());
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -860,7 +1185,7 @@ impl Encode<MI, (Reg32, u32)> for (Op, ModRmIndex, Imm32) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -877,7 +1202,7 @@ impl Encode<MC, (Reg32, Reg8)> for (Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -894,7 +1219,7 @@ impl Encode<RM, (Reg32, Reg8)> for (Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -911,7 +1236,7 @@ impl Encode<RM, (Reg32, Reg16)> for (Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -927,7 +1252,7 @@ impl Encode<XchgSrc, (Reg32, Reg32)> for OpPlusReg {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -943,7 +1268,7 @@ impl Encode<XchgDst, (Reg32, Reg32)> for OpPlusReg {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -959,7 +1284,24 @@ impl Encode<MR, (Reg32, Reg32)> for (Op, ModRm) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MR, (Reg32, Reg32)> for (Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, Reg32), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -976,7 +1318,7 @@ impl Encode<RM, (Reg32, Reg32)> for (Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -991,13 +1333,14 @@ impl<P> Encode<RM, (Reg32, Byte<P>)> for (Op, Op, ModRm)
         let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1012,13 +1355,14 @@ impl<P> Encode<RM, (Reg32, Word<P>)> for (Op, Op, ModRm)
         let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1033,12 +1377,13 @@ impl<P> Encode<RM, (Reg32, DWord<P>)> for (Op, ModRm)
         let (Op(op), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1053,47 +1398,150 @@ impl<P> Encode<RM, (Reg32, DWord<P>)> for (Op, Op, ModRm)
         let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl Encode<M1, (Reg64, u8)> for (RexW, Op, ModRmIndex) {
-    fn encode<E>(emitter: &mut E, (reg, imm): (Reg64, u8), this: Self) -> Result<(), Error<E::Error>>
+impl Encode<MR, (Reg32, Xmm)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(imm == 1);
-        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        if let Some(rex) = rex_b(reg)? {
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg2, reg1)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl Encode<MI, (Reg64, u8)> for (RexW, Op, ModRmIndex, Imm8) {
-    fn encode<E>(emitter: &mut E, (reg, imm): (Reg64, u8), this: Self) -> Result<(), Error<E::Error>>
+impl Encode<RM, (Reg32, Xmm)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (RexW, Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        if let Some(rex) = rex_b(reg)? {
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Reg32, DWord<P>)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Reg32, DWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Reg32, QWord<P>)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Reg32, QWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<M1, (Reg64, u8)> for (RexW, Op, ModRmIndex) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Reg64, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(imm == 1);
+        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MI, (Reg64, u8)> for (RexW, Op, ModRmIndex, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Reg64, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MI, (Reg64, u8)> for (RexW, Op, Op, ModRmIndex, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Reg64, u8), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1108,7 +1556,7 @@ impl Encode<I, (Reg64, u32)> for (RexW, Op, Imm32) {
         buffer.write_u8(0x48);
         buffer.write_u8(op);
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1125,7 +1573,7 @@ impl Encode<MI, (Reg64, u32)> for (RexW, Op, ModRmIndex, Imm32) {
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
         buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1141,7 +1589,7 @@ impl Encode<OI, (Reg64, u64)> for (RexW, OpPlusReg, Imm64) {
         }
         buffer.write_u8(op | reg.rm());
         buffer.write_u64(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1158,7 +1606,7 @@ impl Encode<MC, (Reg64, Reg8)> for (RexW, Op, ModRmIndex) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, modrm_index, reg.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1176,7 +1624,7 @@ impl Encode<RM, (Reg64, Reg8)> for (RexW, Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1194,7 +1642,7 @@ impl Encode<RM, (Reg64, Reg16)> for (RexW, Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1211,7 +1659,7 @@ impl Encode<XchgSrc, (Reg64, Reg64)> for (RexW, OpPlusReg) {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1228,7 +1676,7 @@ impl Encode<XchgDst, (Reg64, Reg64)> for (RexW, OpPlusReg) {
             buffer.write_u8(rex);
         }
         buffer.write_u8(op | reg.rm());
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1245,7 +1693,25 @@ impl Encode<MR, (Reg64, Reg64)> for (RexW, Op, ModRm) {
         }
         buffer.write_u8(op);
         buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MR, (Reg64, Reg64)> for (RexW, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg64, Reg64), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1263,7 +1729,7 @@ impl Encode<RM, (Reg64, Reg64)> for (RexW, Op, Op, ModRm) {
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1278,11 +1744,12 @@ impl<P> Encode<RM, (Reg64, Byte<P>)> for (RexW, Op, Op, ModRm)
         let (RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw_reg(reg)?);
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1297,11 +1764,12 @@ impl<P> Encode<RM, (Reg64, Word<P>)> for (RexW, Op, Op, ModRm)
         let (RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw_reg(reg)?);
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1316,10 +1784,11 @@ impl<P> Encode<RM, (Reg64, QWord<P>)> for (RexW, Op, ModRm)
         let (RexW, Op(op), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw_reg(reg)?);
         buffer.write_u8(op);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -1334,401 +1803,2468 @@ impl<P> Encode<RM, (Reg64, QWord<P>)> for (RexW, Op, Op, ModRm)
         let (RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw_reg(reg)?);
         buffer.write_u8(op1);
         buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<M1, (Byte<P>, u8)> for (Op, ModRmIndex)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (Byte<P>, u8), this: Self)
+impl Encode<MR, (Reg64, Xmm)> for (Prefix, RexW, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg64, Xmm), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(imm == 1);
-        let (Op(op), ModRmIndex(modrm_index)) = this;
-        let ptr = ptr.0;
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg2, reg1)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (Byte<P>, u8)> for (Op, ModRmIndex, Imm8)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (Byte<P>, u8), this: Self)
+impl Encode<RM, (Reg64, Xmm)> for (Prefix, RexW, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg64, Xmm), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Op(op), ModRmIndex(modrm_index), Imm8) = this;
-        let ptr = ptr.0;
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MC, (Byte<P>, Reg8)> for (Op, ModRmIndex)
+impl<P> Encode<RM, (Reg64, DWord<P>)> for (Prefix, RexW, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, cl): (Byte<P>, Reg8), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Reg64, DWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(cl == Reg8::Cl);
-        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
-            buffer.write_u8(rex);
-        }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MR, (Byte<P>, Reg8)> for (Op, ModRm)
+impl<P> Encode<RM, (Reg64, QWord<P>)> for (Prefix, RexW, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, reg): (Byte<P>, Reg8), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Reg64, QWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Op(op), ModRm) = this;
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex_reg(reg)? {
-            buffer.write_u8(rex);
-        }
-        buffer.write_u8(op);
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<M1, (Word<P>, u8)> for (Prefix, Op, ModRmIndex)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u8), this: Self)
-        -> Result<(), Error<E::Error>>
+impl Encode<RM, (Xmm, Xmm)> for (Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Xmm, Xmm), this: Self) -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(imm == 1);
-        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index)) = this;
-        let ptr = ptr.0;
+        let (Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        buffer.write_u8(prefix);
-        if let Some(rex) = ptr.rex()? {
+        if let Some(rex) = rex_rb(reg1, reg2)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (Word<P>, u8)> for (Prefix, Op, ModRmIndex, Imm8)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u8), this: Self)
-        -> Result<(), Error<E::Error>>
+impl Encode<RM, (Xmm, Xmm)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Xmm, Xmm), this: Self) -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index), Imm8) = this;
-        let ptr = ptr.0;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
         buffer.write_u8(prefix);
-        if let Some(rex) = ptr.rex()? {
+        if let Some(rex) = rex_rb(reg1, reg2)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (Word<P>, u16)> for (Prefix, Op, ModRmIndex, Imm16)
+impl<P> Encode<RM, (Xmm, DWord<P>)> for (Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u16), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, DWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index), Imm16) = this;
+        let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(prefix);
-        if let Some(rex) = ptr.rex()? {
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u16(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MC, (Word<P>, Reg8)> for (Prefix, Op, ModRmIndex)
+impl<P> Encode<RM, (Xmm, DWord<P>)> for (Prefix, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, cl): (Word<P>, Reg8), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, DWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(cl == Reg8::Cl);
-        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index)) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(prefix);
-        if let Some(rex) = ptr.rex()? {
+        if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MR, (Word<P>, Reg16)> for (Prefix, Op, ModRm)
+impl<P> Encode<RM, (Xmm, QWord<P>)> for (Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, reg): (Word<P>, Reg16), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, QWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Prefix(prefix), Op(op), ModRm) = this;
+        let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(prefix);
+        Args::write_segment(&mut buffer, &ptr);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<M1, (DWord<P>, u8)> for (Op, ModRmIndex)
+impl<P> Encode<RM, (Xmm, QWord<P>)> for (Prefix, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u8), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, QWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(imm == 1);
-        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (DWord<P>, u8)> for (Op, ModRmIndex, Imm8)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u8), this: Self)
+impl Encode<RM, (Xmm, Reg32)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Xmm, Reg32), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Op(op), ModRmIndex(modrm_index), Imm8) = this;
-        let ptr = ptr.0;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (DWord<P>, u32)> for (Op, ModRmIndex, Imm32)
-    where P: Mem
-{
-    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u32), this: Self)
+impl Encode<RM, (Xmm, Reg64)> for (Prefix, RexW, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Xmm, Reg64), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Op(op), ModRmIndex(modrm_index), Imm32) = this;
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, QWord<P>)> for (Prefix, RexW, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, QWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, XmmWord<P>)> for (Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, XmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MC, (DWord<P>, Reg8)> for (Op, ModRmIndex)
+impl<P> Encode<RM, (Xmm, XmmWord<P>)> for (Prefix, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, cl): (DWord<P>, Reg8), this: Self)
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, XmmWord<P>), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(cl == Reg8::Cl);
-        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        if let Some(rex) = ptr.rex()? {
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MR, (DWord<P>, Reg32)> for (Op, ModRm)
+impl Encode<MRI, (Reg16, Reg16, u8)> for (Prefix, Op, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg16, Reg16, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MRI, (Reg32, Reg32, u8)> for (Op, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg32, Reg32, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MRI, (Reg64, Reg64, u8)> for (RexW, Op, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg64, Reg64, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MRI, (Word<P>, Reg16, u8)> for (Prefix, Op, Op, ModRm, Imm8)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, reg): (DWord<P>, Reg32), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, imm): (Word<P>, Reg16, u8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (Op(op), ModRm) = this;
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm, Imm8) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
         if let Some(rex) = ptr.rex_reg(reg)? {
             buffer.write_u8(rex);
         }
-        buffer.write_u8(op);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<M1, (QWord<P>, u8)> for (RexW, Op, ModRmIndex)
+impl<P> Encode<MRI, (DWord<P>, Reg32, u8)> for (Op, Op, ModRm, Imm8)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u8), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, imm): (DWord<P>, Reg32, u8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        debug_assert!(imm == 1);
-        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let (Op(op1), Op(op2), ModRm, Imm8) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(ptr.rexw()?);
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (QWord<P>, u8)> for (RexW, Op, ModRmIndex, Imm8)
+impl<P> Encode<MRI, (QWord<P>, Reg64, u8)> for (RexW, Op, Op, ModRm, Imm8)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u8), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, imm): (QWord<P>, Reg64, u8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (RexW, Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let (RexW, Op(op1), Op(op2), ModRm, Imm8) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(ptr.rexw()?);
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
         buffer.write_u8(imm);
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MI, (QWord<P>, u32)> for (RexW, Op, ModRmIndex, Imm32)
+impl Encode<MRC, (Reg16, Reg16, Reg8)> for (Prefix, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, cl): (Reg16, Reg16, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MRC, (Reg32, Reg32, Reg8)> for (Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, cl): (Reg32, Reg32, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MRC, (Reg64, Reg64, Reg8)> for (RexW, Op, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, cl): (Reg64, Reg64, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (RexW, Op(op1), Op(op2), ModRm) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg2, reg1)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg2.rm(), reg1.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MRC, (Word<P>, Reg16, Reg8)> for (Prefix, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u32), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, cl): (Word<P>, Reg16, Reg8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (RexW, Op(op), ModRmIndex(modrm_index), Imm32) = this;
+        debug_assert!(cl == Reg8::Cl);
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(ptr.rexw()?);
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        buffer.write_u32(imm);
-        emitter.emit_slice(&buffer)?;
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MC, (QWord<P>, Reg8)> for (RexW, Op, ModRmIndex)
+impl<P> Encode<MRC, (DWord<P>, Reg32, Reg8)> for (Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, cl): (QWord<P>, Reg8), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, cl): (DWord<P>, Reg32, Reg8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
         debug_assert!(cl == Reg8::Cl);
-        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let (Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
-        buffer.write_u8(ptr.rexw()?);
-        buffer.write_u8(op);
-        Args::write(&mut buffer, ptr, modrm_index)?;
-        emitter.emit_slice(&buffer)?;
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
 
-impl<P> Encode<MR, (QWord<P>, Reg64)> for (RexW, Op, ModRm)
+impl<P> Encode<MRC, (QWord<P>, Reg64, Reg8)> for (RexW, Op, Op, ModRm)
     where P: Mem
 {
-    fn encode<E>(emitter: &mut E, (ptr, reg): (QWord<P>, Reg64), this: Self)
+    fn encode<E>(emitter: &mut E, (ptr, reg, cl): (QWord<P>, Reg64, Reg8), this: Self)
         -> Result<(), Error<E::Error>>
         where E: EmitSlice
     {
-        let (RexW, Op(op), ModRm) = this;
+        debug_assert!(cl == Reg8::Cl);
+        let (RexW, Op(op1), Op(op2), ModRm) = this;
         let ptr = ptr.0;
         let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
         buffer.write_u8(ptr.rexw_reg(reg)?);
-        buffer.write_u8(op);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
         Args::write(&mut buffer, ptr, reg.rm())?;
-        emitter.emit_slice(&buffer)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg16, Reg16, u8)> for (Prefix, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg16, Reg16, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg16, Reg16, u16)> for (Prefix, Op, ModRm, Imm16) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg16, Reg16, u16), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRm, Imm16) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u16(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg16, Word<P>, u8)> for (Prefix, Op, ModRm, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg16, Word<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRm, Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg16, Word<P>, u16)> for (Prefix, Op, ModRm, Imm16)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg16, Word<P>, u16), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRm, Imm16) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u16(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg32, Reg32, u8)> for (Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg32, Reg32, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg32, Reg32, u32)> for (Op, ModRm, Imm32) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg32, Reg32, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm, Imm32) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg32, DWord<P>, u8)> for (Op, ModRm, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg32, DWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm, Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg32, DWord<P>, u32)> for (Op, ModRm, Imm32)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg32, DWord<P>, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm, Imm32) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg64, Reg64, u8)> for (RexW, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg64, Reg64, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Reg64, Reg64, u32)> for (RexW, Op, ModRm, Imm32) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Reg64, Reg64, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRm, Imm32) = this;
+        let mut buffer = Buffer::new();
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg64, QWord<P>, u8)> for (RexW, Op, ModRm, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg64, QWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRm, Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Reg64, QWord<P>, u32)> for (RexW, Op, ModRm, Imm32)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Reg64, QWord<P>, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRm, Imm32) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RMI, (Xmm, Xmm, u8)> for (Prefix, Op, Op, ModRm, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, imm): (Xmm, Xmm, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm, Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_rb(reg1, reg2)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMI, (Xmm, XmmWord<P>, u8)> for (Prefix, Op, Op, ModRm, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, imm): (Xmm, XmmWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm, Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<MI, (Xmm, u8)> for (Prefix, Op, Op, ModRmIndex, Imm8) {
+    fn encode<E>(emitter: &mut E, (reg, imm): (Xmm, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let mut buffer = Buffer::new();
+        buffer.write_u8(prefix);
+        if let Some(rex) = rex_b(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        buffer.write_u8(modrm(3, modrm_index, reg.rm()));
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M1, (Byte<P>, u8)> for (Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Byte<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(imm == 1);
+        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (Byte<P>, u8)> for (Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Byte<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MC, (Byte<P>, Reg8)> for (Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, cl): (Byte<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Byte<P>, Reg8)> for (Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (Byte<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Byte<P>, Reg8)> for (Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (Byte<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M1, (Word<P>, u8)> for (Prefix, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(imm == 1);
+        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (Word<P>, u8)> for (Prefix, Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (Word<P>, u8)> for (Prefix, Op, Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (Word<P>, u16)> for (Prefix, Op, ModRmIndex, Imm16)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (Word<P>, u16), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index), Imm16) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u16(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MC, (Word<P>, Reg8)> for (Prefix, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, cl): (Word<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (Prefix(prefix), Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Word<P>, Reg16)> for (Prefix, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (Word<P>, Reg16), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Word<P>, Reg16)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (Word<P>, Reg16), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M1, (DWord<P>, u8)> for (Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(imm == 1);
+        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (DWord<P>, u8)> for (Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (DWord<P>, u8)> for (Op, Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (DWord<P>, u32)> for (Op, ModRmIndex, Imm32)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (DWord<P>, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRmIndex(modrm_index), Imm32) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MC, (DWord<P>, Reg8)> for (Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, cl): (DWord<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex()? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (DWord<P>, Reg32)> for (Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (DWord<P>, Reg32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (DWord<P>, Reg32)> for (Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (DWord<P>, Reg32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (DWord<P>, Xmm)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (DWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<M1, (QWord<P>, u8)> for (RexW, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(imm == 1);
+        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (QWord<P>, u8)> for (RexW, Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (QWord<P>, u8)> for (RexW, Op, Op, ModRmIndex, Imm8)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRmIndex(modrm_index), Imm8) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u8(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MI, (QWord<P>, u32)> for (RexW, Op, ModRmIndex, Imm32)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, imm): (QWord<P>, u32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRmIndex(modrm_index), Imm32) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.write_u32(imm);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MC, (QWord<P>, Reg8)> for (RexW, Op, ModRmIndex)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, cl): (QWord<P>, Reg8), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        debug_assert!(cl == Reg8::Cl);
+        let (RexW, Op(op), ModRmIndex(modrm_index)) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw()?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, modrm_index)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (QWord<P>, Reg64)> for (RexW, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (QWord<P>, Reg64), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (QWord<P>, Reg64)> for (RexW, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (QWord<P>, Reg64), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (RexW, Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(ptr.rexw_reg(reg)?);
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (QWord<P>, Xmm)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (QWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (XmmWord<P>, Xmm)> for (Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (XmmWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (XmmWord<P>, Xmm)> for (Prefix, Op, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (XmmWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Prefix(prefix), Op(op1), Op(op2), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        buffer.write_u8(prefix);
+        if let Some(rex) = ptr.rex_reg(reg)? {
+            buffer.write_u8(rex);
+        }
+        buffer.write_u8(op1);
+        buffer.write_u8(op2);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<None, ()> for (Vex, Op) {
+    fn encode<E>(emitter: &mut E, _: (), this: Self) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op)) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, Option::None, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RVM, (Xmm, Xmm, Xmm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, reg3): (Xmm, Xmm, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg3)?, prefix, map, w, vex_reg(reg2)?, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg3.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RVM, (Ymm, Ymm, Ymm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2, reg3): (Ymm, Ymm, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg3)?, prefix, map, w, vex_reg(reg2)?, true);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg3.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RVM, (Xmm, Xmm, DWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg1, reg2, ptr): (Xmm, Xmm, DWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg1)?, prefix, map, w, vex_reg(reg2)?, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg1.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RVM, (Xmm, Xmm, QWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg1, reg2, ptr): (Xmm, Xmm, QWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg1)?, prefix, map, w, vex_reg(reg2)?, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg1.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RVM, (Xmm, Xmm, XmmWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg1, reg2, ptr): (Xmm, Xmm, XmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg1)?, prefix, map, w, vex_reg(reg2)?, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg1.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RVM, (Ymm, Ymm, YmmWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg1, reg2, ptr): (Ymm, Ymm, YmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg1)?, prefix, map, w, vex_reg(reg2)?, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg1.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (Reg32, Xmm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (Reg32, Ymm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (Xmm, Xmm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Xmm, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (Ymm, Xmm)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Ymm, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, Byte<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, Byte<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, Word<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, Word<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, DWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, DWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Xmm, QWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Xmm, QWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Ymm, Byte<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Ymm, Byte<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Ymm, Word<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Ymm, Word<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Ymm, DWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Ymm, DWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RM, (Ymm, QWord<P>)> for (Vex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr): (Ymm, QWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, 0, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (K, Reg32)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (K, Reg32), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (Reg32, K)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (Reg32, K), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl Encode<RM, (K, K)> for (Vex, Op, ModRm) {
+    fn encode<E>(emitter: &mut E, (reg1, reg2): (K, K), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, rex_rb(reg1, reg2)?, prefix, map, w, 0, false);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RVM, (D, Xmm, Xmm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, reg3): (D, Xmm, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg3), prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg3.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Xmm, XmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Xmm, XmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 16)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Xmm, Broadcast<DWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Xmm, Broadcast<DWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 4)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Xmm, Broadcast<QWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Xmm, Broadcast<QWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 8)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RM, (D, Xmm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2): (D, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg2), prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RM, (D, XmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Xmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, ptr): (D, XmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 16)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (XmmWord<P>, Xmm)> for (Evex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (XmmWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let p2 = evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 16)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RVM, (D, Ymm, Ymm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, reg3): (D, Ymm, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg3), prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg3.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Ymm, YmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Ymm, YmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 32)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Ymm, Broadcast<DWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Ymm, Broadcast<DWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 4)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Ymm, Broadcast<QWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Ymm, Broadcast<QWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 8)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RM, (D, Ymm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2): (D, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg2), prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RM, (D, YmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Ymm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, ptr): (D, YmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 32)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (YmmWord<P>, Ymm)> for (Evex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (YmmWord<P>, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let p2 = evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 32)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RVM, (D, Zmm, Zmm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, reg3): (D, Zmm, Zmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg3), prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg3.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Zmm, ZmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Zmm, ZmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 64)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Zmm, Broadcast<DWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Zmm, Broadcast<DWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 4)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RVM, (D, Zmm, Broadcast<QWord<P>>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2, ptr): (D, Zmm, Broadcast<QWord<P>>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let ptr = ptr.0.0;
+        let p2 = dst.mask() | evex_ll(reg1) << 5 | 0x10;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg1, &ptr)?, prefix, map, w, evex_reg(reg2), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg1.rm(), 8)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D> Encode<RM, (D, Zmm)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg2): (D, Zmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg1 = dst.reg();
+        let p2 = dst.mask() | evex_ll(reg1) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb(reg1, reg2), prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        buffer.write_u8(modrm(3, reg1.rm(), reg2.rm()));
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<D, P> Encode<RM, (D, ZmmWord<P>)> for (Evex, Op, ModRm)
+    where D: EvexDst<Reg = Zmm>, P: Mem
+{
+    fn encode<E>(emitter: &mut E, (dst, ptr): (D, ZmmWord<P>), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let reg = dst.reg();
+        let ptr = ptr.0;
+        let p2 = dst.mask() | evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 64)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (ZmmWord<P>, Zmm)> for (Evex, Op, ModRm)
+    where P: Mem
+{
+    fn encode<E>(emitter: &mut E, (ptr, reg): (ZmmWord<P>, Zmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let p2 = evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        Args::write_segment(&mut buffer, &ptr);
+        write_evex(&mut buffer, evex_rrxb_mem(reg, &ptr)?, prefix, map, w, 0, p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 64)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMV, (Xmm, DWord<P>, Xmm)> for (Vex, Op, ModRm)
+    where P: Vsib<Xmm>
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, mask): (Xmm, DWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let index = ptr.index();
+        if reg == index || reg == mask || index == mask {
+            return Err(Error::InvalidOperands);
+        }
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, vex_reg(mask)?, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMV, (Xmm, QWord<P>, Xmm)> for (Vex, Op, ModRm)
+    where P: Vsib<Xmm>
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, mask): (Xmm, QWord<P>, Xmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let index = ptr.index();
+        if reg == index || reg == mask || index == mask {
+            return Err(Error::InvalidOperands);
+        }
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, vex_reg(mask)?, false);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMV, (Ymm, DWord<P>, Ymm)> for (Vex, Op, ModRm)
+    where P: Vsib<Ymm>
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, mask): (Ymm, DWord<P>, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let index = ptr.index();
+        if reg == index || reg == mask || index == mask {
+            return Err(Error::InvalidOperands);
+        }
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, vex_reg(mask)?, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<RMV, (Ymm, QWord<P>, Ymm)> for (Vex, Op, ModRm)
+    where P: Vsib<Ymm>
+{
+    fn encode<E>(emitter: &mut E, (reg, ptr, mask): (Ymm, QWord<P>, Ymm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Vex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = ptr.0;
+        let index = ptr.index();
+        if reg == index || reg == mask || index == mask {
+            return Err(Error::InvalidOperands);
+        }
+        let mut buffer = Buffer::new();
+        write_vex(&mut buffer, ptr.rex_reg(reg)?, prefix, map, w, vex_reg(mask)?, true);
+        buffer.write_u8(op);
+        Args::write(&mut buffer, ptr, reg.rm())?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Masked<DWord<P>>, Zmm)> for (Evex, Op, ModRm)
+    where P: Vsib<Zmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg): (Masked<DWord<P>>, Zmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = dst.reg.0;
+        let index = ptr.index();
+        if dst.mask == K::K0 || dst.zeroing || reg == index {
+            return Err(Error::InvalidOperands);
+        }
+        let p2 = dst.mask.rm() | evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb_vsib(reg, &ptr), prefix, map, w, evex_vsib_reg(index), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 4)?;
+        buffer.emit(emitter)?;
+        Ok(())
+    }
+}
+
+impl<P> Encode<MR, (Masked<QWord<P>>, Zmm)> for (Evex, Op, ModRm)
+    where P: Vsib<Zmm>
+{
+    fn encode<E>(emitter: &mut E, (dst, reg): (Masked<QWord<P>>, Zmm), this: Self)
+        -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        let (Evex(prefix, map, w), Op(op), ModRm) = this;
+        let ptr = dst.reg.0;
+        let index = ptr.index();
+        if dst.mask == K::K0 || dst.zeroing || reg == index {
+            return Err(Error::InvalidOperands);
+        }
+        let p2 = dst.mask.rm() | evex_ll(reg) << 5;
+        let mut buffer = Buffer::new();
+        write_evex(&mut buffer, evex_rrxb_vsib(reg, &ptr), prefix, map, w, evex_vsib_reg(index), p2);
+        buffer.write_u8(op);
+        Args::write_compressed(&mut buffer, ptr, reg.rm(), 8)?;
+        buffer.emit(emitter)?;
         Ok(())
     }
 }
@@ -2,7 +2,10 @@ use std::error;
 use std::fmt;
 use std::io;
 
-use reg::{Reg8, Reg64};
+use mitte_core;
+
+use reg::{Reg8, Reg64, VectorReg};
+use relax;
 
 
 pub type IoError = Error<io::Error>;
@@ -13,6 +16,13 @@ pub enum Error<E> {
     InvalidOperands,
     RexIncompatibleRegister(Reg8),
     InvalidIndexRegister(Reg64),
+    BranchOutOfRange { offset: i64 },
+    Fixup(mitte_core::Error),
+    LabelAlreadyBound(relax::Label),
+    UnboundLabel(relax::Label),
+    InvalidLabel(relax::Label),
+    PcRelative { pos: usize },
+    EvexOnlyRegister(VectorReg),
     Custom(E),
 }
 
@@ -26,6 +36,19 @@ impl<E> fmt::Display for Error<E>
                 write!(fmt, "register {:?} is incompatible with REX prefix", reg),
             Error::InvalidIndexRegister(reg) =>
                 write!(fmt, "register {:?} can't be used as index", reg),
+            Error::BranchOutOfRange { offset } =>
+                write!(fmt, "branch offset {} is out of range", offset),
+            Error::Fixup(ref error) => write!(fmt, "can't apply fixup: {}", error),
+            Error::LabelAlreadyBound(label) =>
+                write!(fmt, "label {:?} is already bound", label),
+            Error::UnboundLabel(label) =>
+                write!(fmt, "label {:?} is never bound", label),
+            Error::InvalidLabel(label) =>
+                write!(fmt, "label {:?} belongs to another emitter", label),
+            Error::PcRelative { pos } =>
+                write!(fmt, "PC-relative instruction at {} can't be moved by relaxation", pos),
+            Error::EvexOnlyRegister(reg) =>
+                write!(fmt, "register {:?} can only be encoded with EVEX prefix", reg),
             Error::Custom(ref error) => error.fmt(fmt),
         }
     }
@@ -1,29 +1,71 @@
+use std::convert::TryFrom;
 use std::ops::Range;
 
-use mitte_core::Error;
+use mitte_core::{self, Label};
+use error::Error;
 
 
 pub enum FixupKind {
     PcRel8,
     PcRel32,
+    /// A RIP-relative disp32 followed by an immediate of the given size in bytes.
+    RipRel32(u8),
 }
 
-impl<Emit> mitte_core::FixupKind<Emit> for FixupKind
-    where Emit: mitte_core::Emit
-{
-    #[inline]
-    fn apply_fixup(&self, emit: &mut Emit, range: Range<u64>, offset: i64) -> Result<(), Error> {
+impl FixupKind {
+    /// Patches the instruction at `range` to reach `offset` bytes past its start.
+    pub fn apply<E>(&self, emit: &mut E, range: Range<u64>, offset: i64) -> Result<(), Error<E::Error>>
+        where E: mitte_core::Emit
+    {
         let offset = offset - (range.end - range.start) as i64;
         match *self {
             FixupKind::PcRel8 => {
-                let buffer = emit.get_mut_array::<1>(range.end - 1)?;
-                buffer.copy_from_slice(&(offset as i8).to_le_bytes());
+                let rel = i8::try_from(offset).map_err(|_| Error::<E::Error>::BranchOutOfRange { offset })?;
+                let buffer = emit.get_mut_array::<1>(range.end - 1).map_err(Error::<E::Error>::Fixup)?;
+                buffer.copy_from_slice(&rel.to_le_bytes());
             }
             FixupKind::PcRel32 => {
-                let buffer = emit.get_mut_array::<4>(range.end - 4)?;
-                buffer.copy_from_slice(&(offset as i32).to_le_bytes());
+                let rel = i32::try_from(offset).map_err(|_| Error::<E::Error>::BranchOutOfRange { offset })?;
+                let buffer = emit.get_mut_array::<4>(range.end - 4).map_err(Error::<E::Error>::Fixup)?;
+                buffer.copy_from_slice(&rel.to_le_bytes());
+            }
+            FixupKind::RipRel32(imm_size) => {
+                let rel = i32::try_from(offset).map_err(|_| Error::<E::Error>::BranchOutOfRange { offset })?;
+                let buffer = emit.get_mut_array::<4>(range.end - 4 - imm_size as u64).map_err(Error::<E::Error>::Fixup)?;
+                buffer.copy_from_slice(&rel.to_le_bytes());
             }
         }
         Ok(())
     }
 }
+
+impl<Emit> mitte_core::FixupKind<Emit> for FixupKind
+    where Emit: mitte_core::Emit
+{
+    #[inline]
+    fn apply_fixup(&self, emit: &mut Emit, range: Range<u64>, offset: i64) -> Result<(), mitte_core::Error> {
+        // `mitte_core::Error` can't carry `Error::BranchOutOfRange`, so an
+        // offset that doesn't fit fails like a fixup outside of the code.
+        // Use `bind_label` to tell the two apart.
+        match self.apply(emit, range, offset) {
+            Ok(()) => Ok(()),
+            Err(Error::Fixup(error)) => Err(error),
+            Err(_) => Err(mitte_core::Error::OutOfBounds),
+        }
+    }
+}
+
+
+/// Binds `label` to the current position like `mitte_core::Emit::bind_label`,
+/// but fails with `Error::BranchOutOfRange` if a branch to it can't reach.
+pub fn bind_label<E, L>(emit: &mut E, label: &mut L) -> Result<(), Error<E::Error>>
+    where E: mitte_core::Emit, L: Label<E, FixupKind>
+{
+    let pos = emit.cur_pos();
+    label.set_target(pos);
+    for (kind, range) in label.take_fixups() {
+        let offset = pos as i64 - range.start as i64;
+        kind.apply(emit, range, offset)?;
+    }
+    Ok(())
+}
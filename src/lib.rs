@@ -3,6 +3,7 @@ extern crate arrayvec;
 
 use mitte_core::EmitSlice;
 use amd64::*;
+use reg::Reg64;
 
 mod buffer;
 mod ptr;
@@ -15,15 +16,21 @@ pub mod reg;
 pub mod operand;
 pub mod error;
 pub mod fixup;
+pub mod relax;
+pub mod address;
 
 pub mod amd64;
 
-pub use ptr::{Mem, Byte, Word, DWord, QWord};
-pub use ptr::{byte_ptr, word_ptr, dword_ptr, qword_ptr};
+pub use ptr::{Mem, Vsib, Byte, Word, DWord, QWord, XmmWord, YmmWord, ZmmWord, Broadcast, Lock, Seg};
+pub use ptr::{byte_ptr, word_ptr, dword_ptr, qword_ptr, xmmword_ptr, ymmword_ptr, zmmword_ptr};
 pub use ptr::{byte_pointer, word_pointer, dword_pointer, qword_pointer};
-pub use ptr::{Ptr, Pointer};
+pub use ptr::{xmmword_pointer, ymmword_pointer, zmmword_pointer};
+pub use ptr::{Ptr, Pointer, seg};
 pub use operand::Operand;
+pub use amd64::cond::Cond;
 pub use error::Error;
+pub use address::{Address, Based};
+pub use encode::Segmented;
 
 
 macro_rules! forward {
@@ -48,13 +55,22 @@ pub trait Emit: EmitSlice {
         emit_sub(dst: D, src: S) => Sub;
         emit_xor(dst: D, src: S) => Xor;
         emit_cmp(dst: D, src: S) => Cmp;
+        emit_rol(dst: D, src: S) => Rol;
+        emit_ror(dst: D, src: S) => Ror;
+        emit_rcl(dst: D, src: S) => Rcl;
+        emit_rcr(dst: D, src: S) => Rcr;
         emit_shl(dst: D, src: S) => Shl;
+        emit_sal(dst: D, src: S) => Sal;
         emit_shr(dst: D, src: S) => Shr;
         emit_sar(dst: D, src: S) => Sar;
+        emit_shld(dst: D, src: S, shift: C) => Shld;
+        emit_shrd(dst: D, src: S, shift: C) => Shrd;
         emit_not(arg: T) => Not;
         emit_neg(arg: T) => Neg;
         emit_mul(arg: T) => Mul;
         emit_imul(arg: T) => Imul;
+        emit_imul2(dst: D, src: S) => Imul2;
+        emit_imul3(dst: D, src: S, imm: I) => Imul3;
         emit_div(arg: T) => Div;
         emit_idiv(arg: T) => Idiv;
         emit_inc(arg: T) => Inc;
@@ -65,7 +81,11 @@ pub trait Emit: EmitSlice {
         emit_pop(dst: D) => Pop;
         emit_call(arg: T) => Call;
         emit_jmp(arg: T) => Jmp;
+        emit_jmp_short(arg: T) => JmpShort;
         emit_ret() => Ret;
+        emit_ret_imm(imm: T) => RetImm;
+        emit_leave() => Leave;
+        emit_enter(size: S, level: L) => Enter;
 
         emit_cmova(dst: D, src: S) => Cmova;
         emit_cmovae(dst: D, src: S) => Cmovae;
@@ -129,6 +149,37 @@ pub trait Emit: EmitSlice {
         emit_js(arg: T) => Js;
         emit_jz(arg: T) => Jz;
 
+        emit_ja_short(arg: T) => JaShort;
+        emit_jae_short(arg: T) => JaeShort;
+        emit_jb_short(arg: T) => JbShort;
+        emit_jbe_short(arg: T) => JbeShort;
+        emit_jc_short(arg: T) => JcShort;
+        emit_je_short(arg: T) => JeShort;
+        emit_jg_short(arg: T) => JgShort;
+        emit_jge_short(arg: T) => JgeShort;
+        emit_jl_short(arg: T) => JlShort;
+        emit_jle_short(arg: T) => JleShort;
+        emit_jna_short(arg: T) => JnaShort;
+        emit_jnae_short(arg: T) => JnaeShort;
+        emit_jnb_short(arg: T) => JnbShort;
+        emit_jnbe_short(arg: T) => JnbeShort;
+        emit_jnc_short(arg: T) => JncShort;
+        emit_jne_short(arg: T) => JneShort;
+        emit_jng_short(arg: T) => JngShort;
+        emit_jnge_short(arg: T) => JngeShort;
+        emit_jnl_short(arg: T) => JnlShort;
+        emit_jnle_short(arg: T) => JnleShort;
+        emit_jno_short(arg: T) => JnoShort;
+        emit_jnp_short(arg: T) => JnpShort;
+        emit_jns_short(arg: T) => JnsShort;
+        emit_jnz_short(arg: T) => JnzShort;
+        emit_jo_short(arg: T) => JoShort;
+        emit_jp_short(arg: T) => JpShort;
+        emit_jpe_short(arg: T) => JpeShort;
+        emit_jpo_short(arg: T) => JpoShort;
+        emit_js_short(arg: T) => JsShort;
+        emit_jz_short(arg: T) => JzShort;
+
         emit_seta(dst: D) => Seta;
         emit_setae(dst: D) => Setae;
         emit_setb(dst: D) => Setb;
@@ -166,11 +217,198 @@ pub trait Emit: EmitSlice {
 
         emit_bsf(dst: D, src: S) => Bsf;
         emit_bsr(dst: D, src: S) => Bsr;
+        emit_bt(dst: D, src: S) => Bt;
+        emit_bts(dst: D, src: S) => Bts;
+        emit_btr(dst: D, src: S) => Btr;
+        emit_btc(dst: D, src: S) => Btc;
 
+        emit_cbw() => Cbw;
+        emit_cwde() => Cwde;
+        emit_cdqe() => Cdqe;
+        emit_cwd() => Cwd;
         emit_cdq() => Cdq;
+        emit_cqo() => Cqo;
         emit_xchg(dst: D, src: S) => Xchg;
+        emit_xadd(dst: D, src: S) => Xadd;
+        emit_cmpxchg(dst: D, src: S) => Cmpxchg;
+        emit_cmpxchg8b(arg: T) => Cmpxchg8b;
+        emit_cmpxchg16b(arg: T) => Cmpxchg16b;
 
         emit_ud2() => Ud2;
+        emit_movs(size: S) => Movs;
+        emit_cmps(size: S) => Cmps;
+        emit_stos(size: S) => Stos;
+        emit_lods(size: S) => Lods;
+        emit_scas(size: S) => Scas;
+        emit_lfence() => Lfence;
+        emit_mfence() => Mfence;
+        emit_sfence() => Sfence;
+        emit_pause() => Pause;
+        emit_nop(len: T) => Nop;
+        emit_clflush(arg: T) => Clflush;
+        emit_clflushopt(arg: T) => Clflushopt;
+        emit_clwb(arg: T) => Clwb;
+
+        emit_movss(dst: D, src: S) => Movss;
+        emit_movsd(dst: D, src: S) => Movsd;
+        emit_movd(dst: D, src: S) => Movd;
+        emit_movq(dst: D, src: S) => Movq;
+        emit_addss(dst: D, src: S) => Addss;
+        emit_addsd(dst: D, src: S) => Addsd;
+        emit_subss(dst: D, src: S) => Subss;
+        emit_subsd(dst: D, src: S) => Subsd;
+        emit_mulss(dst: D, src: S) => Mulss;
+        emit_mulsd(dst: D, src: S) => Mulsd;
+        emit_divss(dst: D, src: S) => Divss;
+        emit_divsd(dst: D, src: S) => Divsd;
+        emit_sqrtss(dst: D, src: S) => Sqrtss;
+        emit_sqrtsd(dst: D, src: S) => Sqrtsd;
+        emit_minss(dst: D, src: S) => Minss;
+        emit_minsd(dst: D, src: S) => Minsd;
+        emit_maxss(dst: D, src: S) => Maxss;
+        emit_maxsd(dst: D, src: S) => Maxsd;
+        emit_ucomiss(dst: D, src: S) => Ucomiss;
+        emit_ucomisd(dst: D, src: S) => Ucomisd;
+        emit_comiss(dst: D, src: S) => Comiss;
+        emit_comisd(dst: D, src: S) => Comisd;
+
+        emit_cvtsi2ss(dst: D, src: S) => Cvtsi2ss;
+        emit_cvtsi2sd(dst: D, src: S) => Cvtsi2sd;
+        emit_cvtss2si(dst: D, src: S) => Cvtss2si;
+        emit_cvtsd2si(dst: D, src: S) => Cvtsd2si;
+        emit_cvttss2si(dst: D, src: S) => Cvttss2si;
+        emit_cvttsd2si(dst: D, src: S) => Cvttsd2si;
+        emit_cvtss2sd(dst: D, src: S) => Cvtss2sd;
+        emit_cvtsd2ss(dst: D, src: S) => Cvtsd2ss;
+
+        emit_movaps(dst: D, src: S) => Movaps;
+        emit_movups(dst: D, src: S) => Movups;
+        emit_movdqa(dst: D, src: S) => Movdqa;
+        emit_movdqu(dst: D, src: S) => Movdqu;
+
+        emit_addps(dst: D, src: S) => Addps;
+        emit_addpd(dst: D, src: S) => Addpd;
+        emit_mulps(dst: D, src: S) => Mulps;
+        emit_mulpd(dst: D, src: S) => Mulpd;
+        emit_andps(dst: D, src: S) => Andps;
+        emit_xorps(dst: D, src: S) => Xorps;
+        emit_paddb(dst: D, src: S) => Paddb;
+        emit_paddw(dst: D, src: S) => Paddw;
+        emit_paddd(dst: D, src: S) => Paddd;
+        emit_paddq(dst: D, src: S) => Paddq;
+        emit_psubb(dst: D, src: S) => Psubb;
+        emit_psubw(dst: D, src: S) => Psubw;
+        emit_psubd(dst: D, src: S) => Psubd;
+        emit_psubq(dst: D, src: S) => Psubq;
+        emit_pand(dst: D, src: S) => Pand;
+        emit_pandn(dst: D, src: S) => Pandn;
+        emit_por(dst: D, src: S) => Por;
+        emit_pxor(dst: D, src: S) => Pxor;
+        emit_pcmpeqb(dst: D, src: S) => Pcmpeqb;
+        emit_pcmpeqw(dst: D, src: S) => Pcmpeqw;
+        emit_pcmpeqd(dst: D, src: S) => Pcmpeqd;
+        emit_pcmpgtb(dst: D, src: S) => Pcmpgtb;
+        emit_pcmpgtw(dst: D, src: S) => Pcmpgtw;
+        emit_pcmpgtd(dst: D, src: S) => Pcmpgtd;
+        emit_punpcklbw(dst: D, src: S) => Punpcklbw;
+        emit_punpcklwd(dst: D, src: S) => Punpcklwd;
+        emit_punpckldq(dst: D, src: S) => Punpckldq;
+        emit_punpcklqdq(dst: D, src: S) => Punpcklqdq;
+        emit_punpckhbw(dst: D, src: S) => Punpckhbw;
+        emit_punpckhwd(dst: D, src: S) => Punpckhwd;
+        emit_punpckhdq(dst: D, src: S) => Punpckhdq;
+        emit_punpckhqdq(dst: D, src: S) => Punpckhqdq;
+        emit_psllw(dst: D, src: S) => Psllw;
+        emit_pslld(dst: D, src: S) => Pslld;
+        emit_psllq(dst: D, src: S) => Psllq;
+        emit_psrlw(dst: D, src: S) => Psrlw;
+        emit_psrld(dst: D, src: S) => Psrld;
+        emit_psrlq(dst: D, src: S) => Psrlq;
+        emit_pmovmskb(dst: D, src: S) => Pmovmskb;
+        emit_pshufd(dst: D, src: S, imm: I) => Pshufd;
+
+        emit_vaddps(dst: D, src1: S1, src2: S2) => Vaddps;
+        emit_vaddpd(dst: D, src1: S1, src2: S2) => Vaddpd;
+        emit_vmulps(dst: D, src1: S1, src2: S2) => Vmulps;
+        emit_vmulpd(dst: D, src1: S1, src2: S2) => Vmulpd;
+        emit_vfmadd132ss(dst: D, src1: S1, src2: S2) => Vfmadd132ss;
+        emit_vfmadd213ss(dst: D, src1: S1, src2: S2) => Vfmadd213ss;
+        emit_vfmadd231ss(dst: D, src1: S1, src2: S2) => Vfmadd231ss;
+        emit_vfmadd132sd(dst: D, src1: S1, src2: S2) => Vfmadd132sd;
+        emit_vfmadd213sd(dst: D, src1: S1, src2: S2) => Vfmadd213sd;
+        emit_vfmadd231sd(dst: D, src1: S1, src2: S2) => Vfmadd231sd;
+        emit_vfmadd132ps(dst: D, src1: S1, src2: S2) => Vfmadd132ps;
+        emit_vfmadd213ps(dst: D, src1: S1, src2: S2) => Vfmadd213ps;
+        emit_vfmadd231ps(dst: D, src1: S1, src2: S2) => Vfmadd231ps;
+        emit_vfmadd132pd(dst: D, src1: S1, src2: S2) => Vfmadd132pd;
+        emit_vfmadd213pd(dst: D, src1: S1, src2: S2) => Vfmadd213pd;
+        emit_vfmadd231pd(dst: D, src1: S1, src2: S2) => Vfmadd231pd;
+        emit_vpaddd(dst: D, src1: S1, src2: S2) => Vpaddd;
+        emit_vpaddq(dst: D, src1: S1, src2: S2) => Vpaddq;
+        emit_vpxor(dst: D, src1: S1, src2: S2) => Vpxor;
+        emit_vpcmpeqb(dst: D, src1: S1, src2: S2) => Vpcmpeqb;
+        emit_vpmovmskb(dst: D, src: S) => Vpmovmskb;
+        emit_vpbroadcastb(dst: D, src: S) => Vpbroadcastb;
+        emit_vpbroadcastw(dst: D, src: S) => Vpbroadcastw;
+        emit_vpbroadcastd(dst: D, src: S) => Vpbroadcastd;
+        emit_vpbroadcastq(dst: D, src: S) => Vpbroadcastq;
+        emit_vpermd(dst: D, src1: S1, src2: S2) => Vpermd;
+        emit_vpgatherdd(dst: D, src: S, mask: M) => Vpgatherdd;
+        emit_vpgatherqq(dst: D, src: S, mask: M) => Vpgatherqq;
+        emit_vgatherdps(dst: D, src: S, mask: M) => Vgatherdps;
+        emit_vzeroupper() => Vzeroupper;
+
+        emit_vpxord(dst: D, src1: S1, src2: S2) => Vpxord;
+        emit_vpxorq(dst: D, src1: S1, src2: S2) => Vpxorq;
+        emit_vpandd(dst: D, src1: S1, src2: S2) => Vpandd;
+        emit_vpandq(dst: D, src1: S1, src2: S2) => Vpandq;
+        emit_vmovdqu32(dst: D, src: S) => Vmovdqu32;
+        emit_vmovdqu64(dst: D, src: S) => Vmovdqu64;
+        emit_kmovw(dst: D, src: S) => Kmovw;
+        emit_vpscatterdd(dst: D, src: S) => Vpscatterdd;
+        emit_vpscatterqq(dst: D, src: S) => Vpscatterqq;
+    }
+
+    fn emit_cmovcc<D, S>(&mut self, cond: Cond, dst: D, src: S) -> Result<(), Error<Self::Error>>
+        where Self: Cmovcc<D, S>
+    {
+        Cmovcc::emit(self, cond, dst, src)
+    }
+
+    fn emit_jcc<T>(&mut self, cond: Cond, arg: T) -> Result<(), Error<Self::Error>>
+        where Self: Jcc<T>
+    {
+        Jcc::emit(self, cond, arg)
+    }
+
+    fn emit_jcc_short<T>(&mut self, cond: Cond, arg: T) -> Result<(), Error<Self::Error>>
+        where Self: JccShort<T>
+    {
+        JccShort::emit(self, cond, arg)
+    }
+
+    fn emit_align(&mut self, align: u64) -> Result<(), Error<Self::Error>>
+        where Self: Align
+    {
+        Align::emit(self, align)
+    }
+
+    fn emit_call_abs(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>>
+        where Self: CallAbs
+    {
+        CallAbs::emit(self, addr, scratch)
+    }
+
+    fn emit_jmp_abs(&mut self, addr: u64, scratch: Reg64) -> Result<(), Error<Self::Error>>
+        where Self: JmpAbs
+    {
+        JmpAbs::emit(self, addr, scratch)
+    }
+
+    fn emit_setcc<D>(&mut self, cond: Cond, dst: D) -> Result<(), Error<Self::Error>>
+        where Self: Setcc<D>
+    {
+        Setcc::emit(self, cond, dst)
     }
 }
 
@@ -5,7 +5,7 @@ macro_rules! op {
     (
         $Trait:ident
         {
-            $(<$($A:ident : $bound:ident),*>)*
+            $(<$($A:ident : $bound:path),*>)*
             $($arg:ident : $T:ty),+
                 ; assert_eq!($assert_e1:expr, $assert_e2:expr)
                 => ($enc:ty) $($e:expr),*;
@@ -29,7 +29,7 @@ macro_rules! op {
     (
         $Trait:ident
         {
-            $(<$($A:ident : $bound:ident),*>)*
+            $(<$($A:ident : $bound:path),*>)*
             $($arg:ident : $T:ty),* => if ($cond:expr) {
                 ($enc1:ty) $($e1:expr),*
             } else {
@@ -88,7 +88,7 @@ macro_rules! op {
     (
         $Trait:ident
         {
-            $(<$($A:ident : $bound:ident),*>)*
+            $(<$($A:ident : $bound:path),*>)*
             $($arg:ident : $T:ty),* => ($enc:ty) $($e:expr),*;
             $($rest:tt)*
         }
@@ -125,3 +125,90 @@ macro_rules! op {
         op! { $Trait => $R { $($rest)* } }
     };
 }
+
+
+macro_rules! cond_op {
+    ($Trait:ident($cond:ident) {}) => {};
+
+    (
+        $Trait:ident($cond:ident)
+        {
+            $(<$($A:ident : $bound:path),*>)*
+            $($arg:ident : $T:ty),+ => ($enc:ty) $($e:expr),*;
+            $($rest:tt)*
+        }
+    ) => {
+        impl<W $($(, $A)*)*> $Trait<$($T),*> for W
+            where W: mitte_core::EmitSlice $($(, $A : $bound)*)*
+        {
+            fn emit(&mut self, $cond: ::amd64::cond::Cond, $($arg: $T),*)
+                -> ::std::result::Result<(), ::error::Error<W::Error>>
+            {
+                #![allow(unused_parens)]
+                ::encode::Encode::<$enc, _>::encode(self, ( $($arg),* ), ( $($e),* ))
+            }
+        }
+        cond_op! { $Trait($cond) { $($rest)* } }
+    };
+}
+
+
+macro_rules! rip_label_op {
+    ($Trait:ident {}) => {};
+
+    (
+        $Trait:ident
+        {
+            [$p:ident : $Size:ident], $arg:ident : $T:ty => $imm_size:expr;
+            $($rest:tt)*
+        }
+    ) => {
+        impl<'a, W, L> $Trait<$Size<::ptr::Ptr<::reg::Rip, (), &'a mut L>>, $T> for W
+            where W: mitte_core::Emit, L: mitte_core::Label<W, ::fixup::FixupKind>
+        {
+            fn emit(&mut self, $p: $Size<::ptr::Ptr<::reg::Rip, (), &'a mut L>>, $arg: $T)
+                -> ::std::result::Result<(), ::error::Error<W::Error>>
+            {
+                let len = ::common::measure(|m| {
+                    $Trait::emit(m, $Size(::reg::Rip + 0), $arg)
+                })? as i32;
+                self.emit_branch(
+                    $p.0.disp,
+                    ::fixup::FixupKind::RipRel32($imm_size),
+                    |emit, offset| {
+                        $Trait::emit(emit, $Size(::reg::Rip + (offset as i32 - len)), $arg)
+                    },
+                )
+            }
+        }
+        rip_label_op! { $Trait { $($rest)* } }
+    };
+
+    (
+        $Trait:ident
+        {
+            $arg:ident : $T:ty, [$p:ident : $Size:ident];
+            $($rest:tt)*
+        }
+    ) => {
+        impl<'a, W, L> $Trait<$T, $Size<::ptr::Ptr<::reg::Rip, (), &'a mut L>>> for W
+            where W: mitte_core::Emit, L: mitte_core::Label<W, ::fixup::FixupKind>
+        {
+            fn emit(&mut self, $arg: $T, $p: $Size<::ptr::Ptr<::reg::Rip, (), &'a mut L>>)
+                -> ::std::result::Result<(), ::error::Error<W::Error>>
+            {
+                let len = ::common::measure(|m| {
+                    $Trait::emit(m, $arg, $Size(::reg::Rip + 0))
+                })? as i32;
+                self.emit_branch(
+                    $p.0.disp,
+                    ::fixup::FixupKind::RipRel32(0),
+                    |emit, offset| {
+                        $Trait::emit(emit, $arg, $Size(::reg::Rip + (offset as i32 - len)))
+                    },
+                )
+            }
+        }
+        rip_label_op! { $Trait { $($rest)* } }
+    };
+}
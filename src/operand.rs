@@ -1,5 +1,5 @@
-use reg::{Reg8, Reg16, Reg32, Reg64};
-use ptr::{Byte, Word, DWord, QWord};
+use reg::{Reg8, Reg16, Reg32, Reg64, Xmm, Ymm, Zmm, K, Masked};
+use ptr::{Byte, Word, DWord, QWord, XmmWord, YmmWord, ZmmWord, Broadcast};
 use ptr::Pointer;
 
 
@@ -13,6 +13,13 @@ pub enum Operand {
     Reg16(Reg16),
     Reg32(Reg32),
     Reg64(Reg64),
+    Xmm(Xmm),
+    Ymm(Ymm),
+    Zmm(Zmm),
+    K(K),
+    MaskedXmm(Masked<Xmm>),
+    MaskedYmm(Masked<Ymm>),
+    MaskedZmm(Masked<Zmm>),
     Offset8(i8),
     Offset16(i16),
     Offset32(i32),
@@ -21,6 +28,11 @@ pub enum Operand {
     WordPointer(Word<Pointer>),
     DWordPointer(DWord<Pointer>),
     QWordPointer(QWord<Pointer>),
+    XmmWordPointer(XmmWord<Pointer>),
+    YmmWordPointer(YmmWord<Pointer>),
+    ZmmWordPointer(ZmmWord<Pointer>),
+    DWordBroadcast(Broadcast<DWord<Pointer>>),
+    QWordBroadcast(Broadcast<QWord<Pointer>>),
 }
 
 
@@ -39,3 +51,23 @@ pub fn dword_pointer<M>(m: M) -> Operand where M: Into<Pointer> {
 pub fn qword_pointer<M>(m: M) -> Operand where M: Into<Pointer> {
     Operand::QWordPointer(QWord(m.into()))
 }
+
+pub fn xmmword_pointer<M>(m: M) -> Operand where M: Into<Pointer> {
+    Operand::XmmWordPointer(XmmWord(m.into()))
+}
+
+pub fn ymmword_pointer<M>(m: M) -> Operand where M: Into<Pointer> {
+    Operand::YmmWordPointer(YmmWord(m.into()))
+}
+
+pub fn zmmword_pointer<M>(m: M) -> Operand where M: Into<Pointer> {
+    Operand::ZmmWordPointer(ZmmWord(m.into()))
+}
+
+pub fn dword_broadcast<M>(m: M) -> Operand where M: Into<Pointer> {
+    Operand::DWordBroadcast(Broadcast(DWord(m.into())))
+}
+
+pub fn qword_broadcast<M>(m: M) -> Operand where M: Into<Pointer> {
+    Operand::QWordBroadcast(Broadcast(QWord(m.into())))
+}
@@ -1,14 +1,18 @@
 use std::ops;
 
-use reg::Reg64;
+use reg::{Reg64, Rip, Xmm, Ymm, Zmm, Segment};
 
 
 mod private {
-    use common::{Rex, Args};
+    use common::{Rex, Args, VsibArgs};
 
     pub trait MemSealed: Clone + Rex + Args {}
 
     impl<M> MemSealed for M where M: Clone + Rex + Args {}
+
+    pub trait VsibSealed<X>: Clone + Rex + Args + VsibArgs<Index = X> {}
+
+    impl<M, X> VsibSealed<X> for M where M: Clone + Rex + Args + VsibArgs<Index = X> {}
 }
 
 pub trait Mem: private::MemSealed {}
@@ -16,6 +20,7 @@ pub trait Mem: private::MemSealed {}
 impl Mem for Pointer {}
 impl Mem for Ptr<(), (), i8> { }
 impl Mem for Ptr<(), (), i32> {}
+impl Mem for Ptr<Rip, (), i32> {}
 impl Mem for Ptr<Reg64, (), ()> {}
 impl Mem for Ptr<Reg64, (), i8> {}
 impl Mem for Ptr<Reg64, (), i32> {}
@@ -25,6 +30,21 @@ impl Mem for Ptr<(), Scaled<Reg64>, i32> {}
 impl Mem for Ptr<Reg64, Scaled<Reg64>, ()> {}
 impl Mem for Ptr<Reg64, Scaled<Reg64>, i8> {}
 impl Mem for Ptr<Reg64, Scaled<Reg64>, i32> {}
+impl<P> Mem for Seg<P> where P: Mem {}
+
+/// A memory operand with a vector index register, as used by gathers and
+/// scatters, with `X` the type of the index register.
+pub trait Vsib<X>: private::VsibSealed<X> {}
+
+impl Vsib<Xmm> for Ptr<Reg64, Scaled<Xmm>, ()> {}
+impl Vsib<Xmm> for Ptr<Reg64, Scaled<Xmm>, i8> {}
+impl Vsib<Xmm> for Ptr<Reg64, Scaled<Xmm>, i32> {}
+impl Vsib<Ymm> for Ptr<Reg64, Scaled<Ymm>, ()> {}
+impl Vsib<Ymm> for Ptr<Reg64, Scaled<Ymm>, i8> {}
+impl Vsib<Ymm> for Ptr<Reg64, Scaled<Ymm>, i32> {}
+impl Vsib<Zmm> for Ptr<Reg64, Scaled<Zmm>, ()> {}
+impl Vsib<Zmm> for Ptr<Reg64, Scaled<Zmm>, i8> {}
+impl Vsib<Zmm> for Ptr<Reg64, Scaled<Zmm>, i32> {}
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Byte<M>(pub M);
@@ -38,6 +58,29 @@ pub struct DWord<M>(pub M);
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct QWord<M>(pub M);
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct XmmWord<M>(pub M);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct YmmWord<M>(pub M);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ZmmWord<M>(pub M);
+
+/// An EVEX embedded broadcast of a `DWord` or `QWord` memory element to all
+/// elements of the vector, written `{1toN}`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Broadcast<M>(pub M);
+
+/// The memory destination of a read-modify-write instruction, which is
+/// emitted with the LOCK prefix to make it atomic.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Lock<M>(pub M);
+
+/// A memory operand with a segment override, e.g. `fs:[0x28]`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Seg<M>(pub Segment, pub M);
+
 
 pub fn byte_ptr<M, B, X, D>(m: M) -> Byte<Ptr<B, X, D>> where M: Into<Ptr<B, X, D>> {
     Byte(m.into())
@@ -55,6 +98,23 @@ pub fn qword_ptr<M, B, X, D>(m: M) -> QWord<Ptr<B, X, D>> where M: Into<Ptr<B, X
     QWord(m.into())
 }
 
+pub fn xmmword_ptr<M, B, X, D>(m: M) -> XmmWord<Ptr<B, X, D>> where M: Into<Ptr<B, X, D>> {
+    XmmWord(m.into())
+}
+
+pub fn ymmword_ptr<M, B, X, D>(m: M) -> YmmWord<Ptr<B, X, D>> where M: Into<Ptr<B, X, D>> {
+    YmmWord(m.into())
+}
+
+pub fn zmmword_ptr<M, B, X, D>(m: M) -> ZmmWord<Ptr<B, X, D>> where M: Into<Ptr<B, X, D>> {
+    ZmmWord(m.into())
+}
+
+
+pub fn seg<M, B, X, D>(segment: Segment, m: M) -> Seg<Ptr<B, X, D>> where M: Into<Ptr<B, X, D>> {
+    Seg(segment, m.into())
+}
+
 
 pub fn byte_pointer<M>(m: M) -> Byte<Pointer> where M: Into<Pointer> {
     Byte(m.into())
@@ -72,6 +132,18 @@ pub fn qword_pointer<M>(m: M) -> QWord<Pointer> where M: Into<Pointer> {
     QWord(m.into())
 }
 
+pub fn xmmword_pointer<M>(m: M) -> XmmWord<Pointer> where M: Into<Pointer> {
+    XmmWord(m.into())
+}
+
+pub fn ymmword_pointer<M>(m: M) -> YmmWord<Pointer> where M: Into<Pointer> {
+    YmmWord(m.into())
+}
+
+pub fn zmmword_pointer<M>(m: M) -> ZmmWord<Pointer> where M: Into<Pointer> {
+    ZmmWord(m.into())
+}
+
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Scale {
@@ -82,6 +154,20 @@ pub enum Scale {
 }
 
 
+impl Scale {
+    #[inline]
+    fn from_factor(factor: u8) -> Scale {
+        match factor {
+            1 => Scale::_1,
+            2 => Scale::_2,
+            4 => Scale::_4,
+            8 => Scale::_8,
+            _ => panic!("Invalid scale {}. Possible values are 1, 2, 4, 8.", factor)
+        }
+    }
+}
+
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Scaled<R>(pub R, pub Scale);
 
@@ -118,6 +204,13 @@ impl From<i32> for Ptr<(), (), i32> {
     }
 }
 
+impl From<Rip> for Ptr<Rip, (), i32> {
+    #[inline]
+    fn from(base: Rip) -> Ptr<Rip, (), i32> {
+        Ptr::new(base, (), 0)
+    }
+}
+
 impl From<Reg64> for Ptr<Reg64, (), ()> {
     #[inline]
     fn from(base: Reg64) -> Ptr<Reg64, (), ()> {
@@ -162,6 +255,33 @@ impl ops::Sub<i32> for Reg64 {
     }
 }
 
+impl ops::Add<i32> for Rip {
+    type Output = Ptr<Rip, (), i32>;
+
+    #[inline]
+    fn add(self, rhs: i32) -> Ptr<Rip, (), i32> {
+        Ptr::new(self, (), rhs)
+    }
+}
+
+impl ops::Sub<i32> for Rip {
+    type Output = Ptr<Rip, (), i32>;
+
+    #[inline]
+    fn sub(self, rhs: i32) -> Ptr<Rip, (), i32> {
+        Ptr::new(self, (), -rhs)
+    }
+}
+
+impl<'a, L> ops::Add<&'a mut L> for Rip {
+    type Output = Ptr<Rip, (), &'a mut L>;
+
+    #[inline]
+    fn add(self, label: &'a mut L) -> Ptr<Rip, (), &'a mut L> {
+        Ptr::new(self, (), label)
+    }
+}
+
 impl ops::Add<Ptr<(), (), i8>> for Reg64 {
     type Output = Ptr<Reg64, (), i8>;
 
@@ -329,22 +449,88 @@ impl ops::Mul<u8> for Reg64 {
 
     #[inline]
     fn mul(self, rhs: u8) -> Ptr<(), Scaled<Reg64>, ()> {
-        let scale = match rhs {
-            1 => Scale::_1,
-            2 => Scale::_2,
-            4 => Scale::_4,
-            8 => Scale::_8,
-            _ => panic!("Invalid scale {}. Possible values are 1, 2, 4, 8.", rhs)
-        };
-        Ptr::new((), Scaled(self, scale), ())
+        Ptr::new((), Scaled(self, Scale::from_factor(rhs)), ())
     }
 }
 
 
+macro_rules! vsib_ops {
+    ($($R:ident),*) => {
+        $(
+        impl ops::Mul<u8> for $R {
+            type Output = Ptr<(), Scaled<$R>, ()>;
+
+            #[inline]
+            fn mul(self, rhs: u8) -> Ptr<(), Scaled<$R>, ()> {
+                Ptr::new((), Scaled(self, Scale::from_factor(rhs)), ())
+            }
+        }
+
+        impl ops::Add<$R> for Reg64 {
+            type Output = Ptr<Reg64, Scaled<$R>, ()>;
+
+            #[inline]
+            fn add(self, index: $R) -> Ptr<Reg64, Scaled<$R>, ()> {
+                Ptr::new(self, Scaled(index, Scale::_1), ())
+            }
+        }
+
+        impl ops::Add<Ptr<(), Scaled<$R>, ()>> for Reg64 {
+            type Output = Ptr<Reg64, Scaled<$R>, ()>;
+
+            #[inline]
+            fn add(self, p: Ptr<(), Scaled<$R>, ()>) -> Ptr<Reg64, Scaled<$R>, ()> {
+                Ptr::new(self, p.index, ())
+            }
+        }
+
+        impl ops::Add<i8> for Ptr<Reg64, Scaled<$R>, ()> {
+            type Output = Ptr<Reg64, Scaled<$R>, i8>;
+
+            #[inline]
+            fn add(self, rhs: i8) -> Ptr<Reg64, Scaled<$R>, i8> {
+                Ptr::new(self.base, self.index, rhs)
+            }
+        }
+
+        impl ops::Add<i32> for Ptr<Reg64, Scaled<$R>, ()> {
+            type Output = Ptr<Reg64, Scaled<$R>, i32>;
+
+            #[inline]
+            fn add(self, rhs: i32) -> Ptr<Reg64, Scaled<$R>, i32> {
+                Ptr::new(self.base, self.index, rhs)
+            }
+        }
+
+        impl ops::Sub<i8> for Ptr<Reg64, Scaled<$R>, ()> {
+            type Output = Ptr<Reg64, Scaled<$R>, i8>;
+
+            #[inline]
+            fn sub(self, rhs: i8) -> Ptr<Reg64, Scaled<$R>, i8> {
+                Ptr::new(self.base, self.index, -rhs)
+            }
+        }
+
+        impl ops::Sub<i32> for Ptr<Reg64, Scaled<$R>, ()> {
+            type Output = Ptr<Reg64, Scaled<$R>, i32>;
+
+            #[inline]
+            fn sub(self, rhs: i32) -> Ptr<Reg64, Scaled<$R>, i32> {
+                Ptr::new(self.base, self.index, -rhs)
+            }
+        }
+        )*
+    };
+}
+
+vsib_ops!(Xmm, Ymm, Zmm);
+
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Pointer {
     Disp8(i8),
     Disp32(i32),
+    RipDisp32(i32),
     Base(Reg64),
     BaseDisp8(Reg64, i8),
     BaseDisp32(Reg64, i32),
@@ -370,6 +556,13 @@ impl From<i32> for Pointer {
     }
 }
 
+impl From<Rip> for Pointer {
+    #[inline]
+    fn from(_: Rip) -> Pointer {
+        Pointer::RipDisp32(0)
+    }
+}
+
 impl From<Reg64> for Pointer {
     #[inline]
     fn from(base: Reg64) -> Pointer {
@@ -391,6 +584,13 @@ impl From<Ptr<(), (), i32>> for Pointer {
     }
 }
 
+impl From<Ptr<Rip, (), i32>> for Pointer {
+    #[inline]
+    fn from(p: Ptr<Rip, (), i32>) -> Pointer {
+        Pointer::RipDisp32(p.disp)
+    }
+}
+
 impl From<Ptr<Reg64, (), ()>> for Pointer {
     #[inline]
     fn from(p: Ptr<Reg64, (), ()>) -> Pointer {
@@ -4,6 +4,11 @@ pub use self::Reg8::*;
 pub use self::Reg16::*;
 pub use self::Reg32::*;
 pub use self::Reg64::*;
+pub use self::Xmm::*;
+pub use self::Ymm::*;
+pub use self::Zmm::*;
+pub use self::K::*;
+pub use self::Segment::*;
 
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -62,6 +67,11 @@ impl Register for Reg8 {
         *self as u8 >= 8
     }
 
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        false
+    }
+
     #[inline]
     fn rm(&self) -> u8 {
         *self as u8 & 7
@@ -155,6 +165,11 @@ impl Register for Reg16 {
         self.has_extended_index()
     }
 
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        false
+    }
+
     #[inline]
     fn rm(&self) -> u8 {
         *self as u8 & 7
@@ -244,6 +259,11 @@ impl Register for Reg32 {
         self.has_extended_index()
     }
 
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        false
+    }
+
     #[inline]
     fn rm(&self) -> u8 {
         *self as u8 & 7
@@ -357,6 +377,11 @@ impl Register for Reg64 {
         true
     }
 
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        false
+    }
+
     #[inline]
     fn rm(&self) -> u8 {
         *self as u8 & 7
@@ -394,6 +419,522 @@ impl Reg64 {
 }
 
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Xmm {
+    #[default]
+    Xmm0 = 0,
+    Xmm1 = 1,
+    Xmm2 = 2,
+    Xmm3 = 3,
+    Xmm4 = 4,
+    Xmm5 = 5,
+    Xmm6 = 6,
+    Xmm7 = 7,
+    Xmm8 = 8,
+    Xmm9 = 9,
+    Xmm10 = 10,
+    Xmm11 = 11,
+    Xmm12 = 12,
+    Xmm13 = 13,
+    Xmm14 = 14,
+    Xmm15 = 15,
+    Xmm16 = 16,
+    Xmm17 = 17,
+    Xmm18 = 18,
+    Xmm19 = 19,
+    Xmm20 = 20,
+    Xmm21 = 21,
+    Xmm22 = 22,
+    Xmm23 = 23,
+    Xmm24 = 24,
+    Xmm25 = 25,
+    Xmm26 = 26,
+    Xmm27 = 27,
+    Xmm28 = 28,
+    Xmm29 = 29,
+    Xmm30 = 30,
+    Xmm31 = 31,
+}
+
+impl Register for Xmm {
+    #[inline]
+    fn size(&self) -> usize {
+        16
+    }
+
+    #[inline]
+    fn is_64bit(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn has_extended_index(&self) -> bool {
+        *self as u8 & 0x8 != 0
+    }
+
+    #[inline]
+    fn is_encodable_with_rex(&self) -> bool {
+        !self.needs_evex()
+    }
+
+    #[inline]
+    fn needs_rex(&self) -> bool {
+        *self as u8 >= 8
+    }
+
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        *self as u8 & 0x10 != 0
+    }
+
+    #[inline]
+    fn rm(&self) -> u8 {
+        *self as u8 & 7
+    }
+
+    #[inline]
+    fn check_is_rex_compatible(&self) -> Result<(), Error<NoError>> {
+        if self.is_encodable_with_rex() {
+            Ok(())
+        } else {
+            Err(Error::EvexOnlyRegister(VectorReg::Xmm(*self)))
+        }
+    }
+}
+
+impl Xmm {
+    #[inline]
+    pub fn from_index(index: usize) -> Option<Xmm> {
+        match index {
+            0 => Some(Xmm::Xmm0),
+            1 => Some(Xmm::Xmm1),
+            2 => Some(Xmm::Xmm2),
+            3 => Some(Xmm::Xmm3),
+            4 => Some(Xmm::Xmm4),
+            5 => Some(Xmm::Xmm5),
+            6 => Some(Xmm::Xmm6),
+            7 => Some(Xmm::Xmm7),
+            8 => Some(Xmm::Xmm8),
+            9 => Some(Xmm::Xmm9),
+            10 => Some(Xmm::Xmm10),
+            11 => Some(Xmm::Xmm11),
+            12 => Some(Xmm::Xmm12),
+            13 => Some(Xmm::Xmm13),
+            14 => Some(Xmm::Xmm14),
+            15 => Some(Xmm::Xmm15),
+            16 => Some(Xmm::Xmm16),
+            17 => Some(Xmm::Xmm17),
+            18 => Some(Xmm::Xmm18),
+            19 => Some(Xmm::Xmm19),
+            20 => Some(Xmm::Xmm20),
+            21 => Some(Xmm::Xmm21),
+            22 => Some(Xmm::Xmm22),
+            23 => Some(Xmm::Xmm23),
+            24 => Some(Xmm::Xmm24),
+            25 => Some(Xmm::Xmm25),
+            26 => Some(Xmm::Xmm26),
+            27 => Some(Xmm::Xmm27),
+            28 => Some(Xmm::Xmm28),
+            29 => Some(Xmm::Xmm29),
+            30 => Some(Xmm::Xmm30),
+            31 => Some(Xmm::Xmm31),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Ymm {
+    #[default]
+    Ymm0 = 0,
+    Ymm1 = 1,
+    Ymm2 = 2,
+    Ymm3 = 3,
+    Ymm4 = 4,
+    Ymm5 = 5,
+    Ymm6 = 6,
+    Ymm7 = 7,
+    Ymm8 = 8,
+    Ymm9 = 9,
+    Ymm10 = 10,
+    Ymm11 = 11,
+    Ymm12 = 12,
+    Ymm13 = 13,
+    Ymm14 = 14,
+    Ymm15 = 15,
+    Ymm16 = 16,
+    Ymm17 = 17,
+    Ymm18 = 18,
+    Ymm19 = 19,
+    Ymm20 = 20,
+    Ymm21 = 21,
+    Ymm22 = 22,
+    Ymm23 = 23,
+    Ymm24 = 24,
+    Ymm25 = 25,
+    Ymm26 = 26,
+    Ymm27 = 27,
+    Ymm28 = 28,
+    Ymm29 = 29,
+    Ymm30 = 30,
+    Ymm31 = 31,
+}
+
+impl Register for Ymm {
+    #[inline]
+    fn size(&self) -> usize {
+        32
+    }
+
+    #[inline]
+    fn is_64bit(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn has_extended_index(&self) -> bool {
+        *self as u8 & 0x8 != 0
+    }
+
+    #[inline]
+    fn is_encodable_with_rex(&self) -> bool {
+        !self.needs_evex()
+    }
+
+    #[inline]
+    fn needs_rex(&self) -> bool {
+        *self as u8 >= 8
+    }
+
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        *self as u8 & 0x10 != 0
+    }
+
+    #[inline]
+    fn rm(&self) -> u8 {
+        *self as u8 & 7
+    }
+
+    #[inline]
+    fn check_is_rex_compatible(&self) -> Result<(), Error<NoError>> {
+        if self.is_encodable_with_rex() {
+            Ok(())
+        } else {
+            Err(Error::EvexOnlyRegister(VectorReg::Ymm(*self)))
+        }
+    }
+}
+
+impl Ymm {
+    #[inline]
+    pub fn from_index(index: usize) -> Option<Ymm> {
+        match index {
+            0 => Some(Ymm::Ymm0),
+            1 => Some(Ymm::Ymm1),
+            2 => Some(Ymm::Ymm2),
+            3 => Some(Ymm::Ymm3),
+            4 => Some(Ymm::Ymm4),
+            5 => Some(Ymm::Ymm5),
+            6 => Some(Ymm::Ymm6),
+            7 => Some(Ymm::Ymm7),
+            8 => Some(Ymm::Ymm8),
+            9 => Some(Ymm::Ymm9),
+            10 => Some(Ymm::Ymm10),
+            11 => Some(Ymm::Ymm11),
+            12 => Some(Ymm::Ymm12),
+            13 => Some(Ymm::Ymm13),
+            14 => Some(Ymm::Ymm14),
+            15 => Some(Ymm::Ymm15),
+            16 => Some(Ymm::Ymm16),
+            17 => Some(Ymm::Ymm17),
+            18 => Some(Ymm::Ymm18),
+            19 => Some(Ymm::Ymm19),
+            20 => Some(Ymm::Ymm20),
+            21 => Some(Ymm::Ymm21),
+            22 => Some(Ymm::Ymm22),
+            23 => Some(Ymm::Ymm23),
+            24 => Some(Ymm::Ymm24),
+            25 => Some(Ymm::Ymm25),
+            26 => Some(Ymm::Ymm26),
+            27 => Some(Ymm::Ymm27),
+            28 => Some(Ymm::Ymm28),
+            29 => Some(Ymm::Ymm29),
+            30 => Some(Ymm::Ymm30),
+            31 => Some(Ymm::Ymm31),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Zmm {
+    #[default]
+    Zmm0 = 0,
+    Zmm1 = 1,
+    Zmm2 = 2,
+    Zmm3 = 3,
+    Zmm4 = 4,
+    Zmm5 = 5,
+    Zmm6 = 6,
+    Zmm7 = 7,
+    Zmm8 = 8,
+    Zmm9 = 9,
+    Zmm10 = 10,
+    Zmm11 = 11,
+    Zmm12 = 12,
+    Zmm13 = 13,
+    Zmm14 = 14,
+    Zmm15 = 15,
+    Zmm16 = 16,
+    Zmm17 = 17,
+    Zmm18 = 18,
+    Zmm19 = 19,
+    Zmm20 = 20,
+    Zmm21 = 21,
+    Zmm22 = 22,
+    Zmm23 = 23,
+    Zmm24 = 24,
+    Zmm25 = 25,
+    Zmm26 = 26,
+    Zmm27 = 27,
+    Zmm28 = 28,
+    Zmm29 = 29,
+    Zmm30 = 30,
+    Zmm31 = 31,
+}
+
+impl Register for Zmm {
+    #[inline]
+    fn size(&self) -> usize {
+        64
+    }
+
+    #[inline]
+    fn is_64bit(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn has_extended_index(&self) -> bool {
+        *self as u8 & 0x8 != 0
+    }
+
+    #[inline]
+    fn is_encodable_with_rex(&self) -> bool {
+        !self.needs_evex()
+    }
+
+    #[inline]
+    fn needs_rex(&self) -> bool {
+        *self as u8 >= 8
+    }
+
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        *self as u8 & 0x10 != 0
+    }
+
+    #[inline]
+    fn rm(&self) -> u8 {
+        *self as u8 & 7
+    }
+
+    #[inline]
+    fn check_is_rex_compatible(&self) -> Result<(), Error<NoError>> {
+        if self.is_encodable_with_rex() {
+            Ok(())
+        } else {
+            Err(Error::EvexOnlyRegister(VectorReg::Zmm(*self)))
+        }
+    }
+}
+
+impl Zmm {
+    #[inline]
+    pub fn from_index(index: usize) -> Option<Zmm> {
+        match index {
+            0 => Some(Zmm::Zmm0),
+            1 => Some(Zmm::Zmm1),
+            2 => Some(Zmm::Zmm2),
+            3 => Some(Zmm::Zmm3),
+            4 => Some(Zmm::Zmm4),
+            5 => Some(Zmm::Zmm5),
+            6 => Some(Zmm::Zmm6),
+            7 => Some(Zmm::Zmm7),
+            8 => Some(Zmm::Zmm8),
+            9 => Some(Zmm::Zmm9),
+            10 => Some(Zmm::Zmm10),
+            11 => Some(Zmm::Zmm11),
+            12 => Some(Zmm::Zmm12),
+            13 => Some(Zmm::Zmm13),
+            14 => Some(Zmm::Zmm14),
+            15 => Some(Zmm::Zmm15),
+            16 => Some(Zmm::Zmm16),
+            17 => Some(Zmm::Zmm17),
+            18 => Some(Zmm::Zmm18),
+            19 => Some(Zmm::Zmm19),
+            20 => Some(Zmm::Zmm20),
+            21 => Some(Zmm::Zmm21),
+            22 => Some(Zmm::Zmm22),
+            23 => Some(Zmm::Zmm23),
+            24 => Some(Zmm::Zmm24),
+            25 => Some(Zmm::Zmm25),
+            26 => Some(Zmm::Zmm26),
+            27 => Some(Zmm::Zmm27),
+            28 => Some(Zmm::Zmm28),
+            29 => Some(Zmm::Zmm29),
+            30 => Some(Zmm::Zmm30),
+            31 => Some(Zmm::Zmm31),
+            _ => None,
+        }
+    }
+}
+
+
+/// An AVX-512 opmask register.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum K {
+    #[default]
+    K0 = 0,
+    K1 = 1,
+    K2 = 2,
+    K3 = 3,
+    K4 = 4,
+    K5 = 5,
+    K6 = 6,
+    K7 = 7,
+}
+
+impl Register for K {
+    #[inline]
+    fn size(&self) -> usize {
+        8
+    }
+
+    #[inline]
+    fn is_64bit(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn has_extended_index(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn is_encodable_with_rex(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn needs_rex(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn needs_evex(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn rm(&self) -> u8 {
+        *self as u8
+    }
+
+    #[inline]
+    fn check_is_rex_compatible(&self) -> Result<(), Error<NoError>> {
+        Ok(())
+    }
+}
+
+impl K {
+    #[inline]
+    pub fn from_index(index: usize) -> Option<K> {
+        match index {
+            0 => Some(K::K0),
+            1 => Some(K::K1),
+            2 => Some(K::K2),
+            3 => Some(K::K3),
+            4 => Some(K::K4),
+            5 => Some(K::K5),
+            6 => Some(K::K6),
+            7 => Some(K::K7),
+            _ => None,
+        }
+    }
+}
+
+
+/// A vector register, as reported by `Error::EvexOnlyRegister`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum VectorReg {
+    Xmm(Xmm),
+    Ymm(Ymm),
+    Zmm(Zmm),
+}
+
+
+/// A destination register written under an opmask, `reg {k}` or
+/// `reg {k}{z}` when `zeroing` is set. Scatters use it with a memory
+/// destination instead.
+///
+/// Masking with `K0` is not encodable and means no masking.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Masked<R> {
+    pub reg: R,
+    pub mask: K,
+    pub zeroing: bool,
+}
+
+impl<R> Masked<R> {
+    #[inline]
+    pub fn merge(reg: R, mask: K) -> Masked<R> {
+        Masked {
+            reg,
+            mask,
+            zeroing: false,
+        }
+    }
+
+    #[inline]
+    pub fn zero(reg: R, mask: K) -> Masked<R> {
+        Masked {
+            reg,
+            mask,
+            zeroing: true,
+        }
+    }
+}
+
+
+/// The instruction pointer, usable only as the base of a memory operand.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rip;
+
+
+/// A segment register, usable only as the segment override of a memory
+/// operand. In 64-bit mode only `Fs` and `Gs` have a nonzero base.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Segment {
+    Es = 0x26,
+    Cs = 0x2e,
+    Ss = 0x36,
+    Ds = 0x3e,
+    Fs = 0x64,
+    Gs = 0x65,
+}
+
+impl Segment {
+    #[inline]
+    pub(crate) fn prefix(self) -> u8 {
+        self as u8
+    }
+}
+
+
 impl From<Reg16> for Reg8 {
     #[inline]
     fn from(reg: Reg16) -> Reg8 {
@@ -0,0 +1,244 @@
+use std::cell::Cell;
+use std::convert::Infallible;
+
+use mitte_core::EmitSlice;
+
+use error::Error;
+use amd64::cond::Cond;
+use encode::Encode;
+use encode::{D, Op, Imm8, Imm32};
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Label(usize);
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Jump {
+    Call,
+    Jmp,
+    Jcc(Cond),
+}
+
+impl Jump {
+    fn short_size(&self) -> usize {
+        match *self {
+            Jump::Call => 5,
+            Jump::Jmp | Jump::Jcc(_) => 2,
+        }
+    }
+
+    fn long_size(&self) -> usize {
+        match *self {
+            Jump::Call | Jump::Jmp => 5,
+            Jump::Jcc(_) => 6,
+        }
+    }
+}
+
+
+struct Branch {
+    jump: Jump,
+    target: Label,
+    /// Offset of the branch in the recorded code.
+    pos: usize,
+}
+
+
+/// Records code with label branches and picks the shortest branch encodings
+/// once all labels are bound.
+///
+/// Everything except branches to a `relax::Label` is emitted as-is.
+/// Branches start out as rel8 and are widened to rel32 until all of them
+/// reach their targets, after which `finish` writes the final code.
+///
+/// Relaxation moves the recorded code, so other PC-relative instructions,
+/// like branches with explicit offsets or `[rip + disp]` operands, can't be
+/// mixed with label branches. `finish` refuses such code with
+/// `Error::PcRelative`. Bytes passed to `emit_slice` directly are assumed to
+/// be position independent.
+pub struct Relax {
+    code: Vec<u8>,
+    branches: Vec<Branch>,
+    /// Offset in the recorded code and number of preceding branches.
+    labels: Vec<Option<(usize, usize)>>,
+    /// Offset of the first PC-relative instruction in the recorded code.
+    pc_relative: Option<usize>,
+}
+
+impl Relax {
+    pub fn new() -> Relax {
+        Relax {
+            code: Vec::new(),
+            branches: Vec::new(),
+            labels: Vec::new(),
+            pc_relative: None,
+        }
+    }
+
+    pub fn new_label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    pub fn bind_label(&mut self, label: Label) -> Result<(), Error<Infallible>> {
+        let pos = (self.code.len(), self.branches.len());
+        match self.labels.get_mut(label.0) {
+            Some(&mut Some(_)) => Err(Error::LabelAlreadyBound(label)),
+            Some(target) => {
+                *target = Some(pos);
+                Ok(())
+            }
+            _ => Err(Error::InvalidLabel(label)),
+        }
+    }
+
+    pub fn finish<E>(&self, emitter: &mut E) -> Result<(), Error<E::Error>>
+        where E: EmitSlice
+    {
+        if let Some(branch) = self.branches.iter().find(|b| self.labels[b.target.0].is_none()) {
+            return Err(Error::UnboundLabel(branch.target));
+        }
+        if let (Some(pos), false) = (self.pc_relative, self.branches.is_empty()) {
+            return Err(Error::PcRelative { pos });
+        }
+
+        let mut long = vec![false; self.branches.len()];
+        let mut starts = vec![0; self.branches.len() + 1];
+
+        loop {
+            let mut growth = 0;
+            for (i, branch) in self.branches.iter().enumerate() {
+                starts[i] = branch.pos + growth;
+                growth += self.size(branch, long[i]);
+            }
+            starts[self.branches.len()] = growth;
+
+            let mut changed = false;
+            for (i, branch) in self.branches.iter().enumerate() {
+                if !long[i] {
+                    let rel = self.target(branch.target, &starts) -
+                        (starts[i] + self.size(branch, false)) as i64;
+                    if rel < i8::MIN as i64 || rel > i8::MAX as i64 {
+                        long[i] = true;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let mut pos = 0;
+        for (i, branch) in self.branches.iter().enumerate() {
+            emitter.emit_slice(&self.code[pos..branch.pos])?;
+            pos = branch.pos;
+
+            let rel = self.target(branch.target, &starts) -
+                (starts[i] + self.size(branch, long[i])) as i64;
+            match (branch.jump, long[i]) {
+                (Jump::Call, _) => {
+                    Encode::<D, _>::encode(emitter, rel as i32, (Op(0xe8), Imm32))?;
+                }
+                (Jump::Jmp, false) => {
+                    Encode::<D, _>::encode(emitter, rel as i8, (Op(0xeb), Imm8))?;
+                }
+                (Jump::Jmp, true) => {
+                    Encode::<D, _>::encode(emitter, rel as i32, (Op(0xe9), Imm32))?;
+                }
+                (Jump::Jcc(cond), false) => {
+                    Encode::<D, _>::encode(emitter, rel as i8, (Op(0x70 | cond.code()), Imm8))?;
+                }
+                (Jump::Jcc(cond), true) => {
+                    Encode::<D, _>::encode(
+                        emitter, rel as i32, (Op(0x0f), Op(0x80 | cond.code()), Imm32))?;
+                }
+            }
+        }
+        emitter.emit_slice(&self.code[pos..])?;
+
+        Ok(())
+    }
+
+    pub(crate) fn push_call(&mut self, target: Label) -> Result<(), Error<Infallible>> {
+        self.push(Jump::Call, target)
+    }
+
+    pub(crate) fn push_jmp(&mut self, target: Label) -> Result<(), Error<Infallible>> {
+        self.push(Jump::Jmp, target)
+    }
+
+    pub(crate) fn push_jcc(&mut self, cond: Cond, target: Label) -> Result<(), Error<Infallible>> {
+        self.push(Jump::Jcc(cond), target)
+    }
+
+    fn push(&mut self, jump: Jump, target: Label) -> Result<(), Error<Infallible>> {
+        if target.0 >= self.labels.len() {
+            return Err(Error::InvalidLabel(target));
+        }
+        self.branches.push(Branch {
+            jump,
+            target,
+            pos: self.code.len(),
+        });
+        Ok(())
+    }
+
+    fn size(&self, branch: &Branch, long: bool) -> usize {
+        if long {
+            branch.jump.long_size()
+        } else {
+            branch.jump.short_size()
+        }
+    }
+
+    /// Returns the final position of `label`, where `starts` holds the final
+    /// position of each branch followed by the total size of all branches.
+    fn target(&self, label: Label, starts: &[usize]) -> i64 {
+        let (pos, branches) = self.labels[label.0].expect("branch target is bound");
+        let growth = if branches < self.branches.len() {
+            starts[branches] - self.branches[branches].pos
+        } else {
+            starts[branches]
+        };
+        (pos + growth) as i64
+    }
+}
+
+impl Default for Relax {
+    fn default() -> Relax {
+        Relax::new()
+    }
+}
+
+impl EmitSlice for Relax {
+    type Error = Infallible;
+
+    #[inline]
+    fn emit_slice(&mut self, slice: &[u8]) -> Result<(), Infallible> {
+        if self.pc_relative.is_none() && PC_RELATIVE.with(Cell::get) {
+            self.pc_relative = Some(self.code.len());
+        }
+        self.code.extend_from_slice(slice);
+        Ok(())
+    }
+}
+
+
+thread_local! {
+    /// Set while a PC-relative instruction is emitted.
+    static PC_RELATIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f`, which emits a single instruction with a branch offset or a
+/// RIP-relative operand, so that a `Relax` recording it can refuse to move it.
+pub(crate) fn emit_pc_relative<F, T>(f: F) -> T
+    where F: FnOnce() -> T
+{
+    let outer = PC_RELATIVE.with(|flag| flag.replace(true));
+    let result = f();
+    PC_RELATIVE.with(|flag| flag.set(outer));
+    result
+}
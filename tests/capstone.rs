@@ -1,9 +1,24 @@
+extern crate mitte_core;
 extern crate mitte_amd64;
 extern crate capstone;
 
+use std::io::Cursor;
+
+use mitte_core::Emit as CoreEmit;
+use mitte_core::label::OptionLabel;
 use mitte_amd64::{Emit, Error};
+use mitte_amd64::Cond;
+use mitte_amd64::amd64::cond;
+use mitte_amd64::fixup;
+use mitte_amd64::amd64::string::{self, Rep, Repe, Repne};
+use mitte_amd64::{dword_ptr, qword_ptr};
+use mitte_amd64::{Pointer, Lock, Based};
+use mitte_amd64::{Byte, Word, DWord, QWord, Seg, seg, Segmented};
+use mitte_amd64::relax::Relax;
 use mitte_amd64::reg::*;
 use mitte_amd64::operand::{Operand, byte_pointer, word_pointer, dword_pointer, qword_pointer};
+use mitte_amd64::operand::{xmmword_pointer, ymmword_pointer, zmmword_pointer};
+use mitte_amd64::operand::{dword_broadcast, qword_broadcast};
 
 use capstone::Capstone;
 use capstone::arch::BuildsCapstone;
@@ -152,6 +167,78 @@ const SIMPLE_REGS64: [(Reg64, &'static str); 8] = [
 ];
 
 
+const XMMS: [(Xmm, &'static str); 16] = [
+    (Xmm0, "xmm0"),
+    (Xmm1, "xmm1"),
+    (Xmm2, "xmm2"),
+    (Xmm3, "xmm3"),
+    (Xmm4, "xmm4"),
+    (Xmm5, "xmm5"),
+    (Xmm6, "xmm6"),
+    (Xmm7, "xmm7"),
+    (Xmm8, "xmm8"),
+    (Xmm9, "xmm9"),
+    (Xmm10, "xmm10"),
+    (Xmm11, "xmm11"),
+    (Xmm12, "xmm12"),
+    (Xmm13, "xmm13"),
+    (Xmm14, "xmm14"),
+    (Xmm15, "xmm15"),
+];
+
+const YMMS: [(Ymm, &'static str); 16] = [
+    (Ymm0, "ymm0"),
+    (Ymm1, "ymm1"),
+    (Ymm2, "ymm2"),
+    (Ymm3, "ymm3"),
+    (Ymm4, "ymm4"),
+    (Ymm5, "ymm5"),
+    (Ymm6, "ymm6"),
+    (Ymm7, "ymm7"),
+    (Ymm8, "ymm8"),
+    (Ymm9, "ymm9"),
+    (Ymm10, "ymm10"),
+    (Ymm11, "ymm11"),
+    (Ymm12, "ymm12"),
+    (Ymm13, "ymm13"),
+    (Ymm14, "ymm14"),
+    (Ymm15, "ymm15"),
+];
+
+
+const EVEX_XMMS: [(Xmm, &'static str); 8] = [
+    (Xmm0, "xmm0"),
+    (Xmm7, "xmm7"),
+    (Xmm8, "xmm8"),
+    (Xmm15, "xmm15"),
+    (Xmm16, "xmm16"),
+    (Xmm23, "xmm23"),
+    (Xmm24, "xmm24"),
+    (Xmm31, "xmm31"),
+];
+
+const EVEX_YMMS: [(Ymm, &'static str); 8] = [
+    (Ymm0, "ymm0"),
+    (Ymm7, "ymm7"),
+    (Ymm8, "ymm8"),
+    (Ymm15, "ymm15"),
+    (Ymm16, "ymm16"),
+    (Ymm23, "ymm23"),
+    (Ymm24, "ymm24"),
+    (Ymm31, "ymm31"),
+];
+
+const EVEX_ZMMS: [(Zmm, &'static str); 8] = [
+    (Zmm0, "zmm0"),
+    (Zmm7, "zmm7"),
+    (Zmm8, "zmm8"),
+    (Zmm15, "zmm15"),
+    (Zmm16, "zmm16"),
+    (Zmm23, "zmm23"),
+    (Zmm24, "zmm24"),
+    (Zmm31, "zmm31"),
+];
+
 fn print_code(code: &[u8]) {
     print!("[");
     for b in code {
@@ -284,6 +371,13 @@ fn test_unit(mnemonic: &str, f: fn(&mut Vec<u8>) -> Result<()>) {
 }
 
 
+fn test_string(mnemonic: &str, operands: &str, f: fn(&mut Vec<u8>) -> Result<()>) {
+    let mut code = Vec::new();
+    f(&mut code).unwrap();
+    test_disasm(mnemonic, &[Some(operands)], &code);
+}
+
+
 fn test_imm8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand) -> Result<()>) {
     let mut code = Vec::new();
     f(&mut code, Operand::Imm8(0x42)).unwrap();
@@ -352,6 +446,11 @@ fn test_byte_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand) -> Result<()>) {
     let expected_disasm = vec![Some("byte ptr [0x12345678]")];
     test_disasm(mnemonic, &expected_disasm, &code);
 
+    let mut code = Vec::new();
+    f(&mut code, byte_pointer(Rip + 0x12345678)).unwrap();
+    let expected_disasm = vec![Some("byte ptr [rip + 0x12345678]")];
+    test_disasm(mnemonic, &expected_disasm, &code);
+
     test_reg(mnemonic, f, REGS64,
              |r| byte_pointer(r),
              |s| format!("byte ptr [{}]", s));
@@ -439,6 +538,11 @@ fn test_word_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand) -> Result<()>) {
     let expected_disasm = vec![Some("word ptr [0x12345678]")];
     test_disasm(mnemonic, &expected_disasm, &code);
 
+    let mut code = Vec::new();
+    f(&mut code, word_pointer(Rip + 0x12345678)).unwrap();
+    let expected_disasm = vec![Some("word ptr [rip + 0x12345678]")];
+    test_disasm(mnemonic, &expected_disasm, &code);
+
     test_reg(mnemonic, f, REGS64,
              |r| word_pointer(r),
              |s| format!("word ptr [{}]", s));
@@ -524,6 +628,11 @@ fn test_dword_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand) -> Result<()>) {
     let expected_disasm = vec![Some("dword ptr [0x12345678]")];
     test_disasm(mnemonic, &expected_disasm, &code);
 
+    let mut code = Vec::new();
+    f(&mut code, dword_pointer(Rip + 0x12345678)).unwrap();
+    let expected_disasm = vec![Some("dword ptr [rip + 0x12345678]")];
+    test_disasm(mnemonic, &expected_disasm, &code);
+
     test_reg(mnemonic, f, REGS64,
              |r| dword_pointer(r),
              |s| format!("dword ptr [{}]", s));
@@ -611,6 +720,11 @@ fn test_qword_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand) -> Result<()>) {
     let expected_disasm = vec![Some("qword ptr [0x12345678]")];
     test_disasm(mnemonic, &expected_disasm, &code);
 
+    let mut code = Vec::new();
+    f(&mut code, qword_pointer(Rip + 0x12345678)).unwrap();
+    let expected_disasm = vec![Some("qword ptr [rip + 0x12345678]")];
+    test_disasm(mnemonic, &expected_disasm, &code);
+
     test_reg(mnemonic, f, REGS64,
              |r| qword_pointer(r),
              |s| format!("qword ptr [{}]", s));
@@ -834,6 +948,11 @@ fn test_reg8_byte_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> R
              |r1| (Operand::Reg8(r1), byte_pointer(0x12345678)),
              |s1| format!("{}, byte ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS8,
+             |r1| (Operand::Reg8(r1), byte_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, byte ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS8, SIMPLE_REGS64,
                  |r1, r2| (Operand::Reg8(r1), byte_pointer(r2)),
@@ -931,6 +1050,11 @@ fn test_reg16_word_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg16(r1), word_pointer(0x12345678)),
              |s1| format!("{}, word ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS16,
+             |r1| (Operand::Reg16(r1), word_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, word ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS16, REGS64,
                  |r1, r2| (Operand::Reg16(r1), word_pointer(r2)),
@@ -992,6 +1116,11 @@ fn test_reg32_dword_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg32(r1), dword_pointer(0x12345678)),
              |s1| format!("{}, dword ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS32,
+             |r1| (Operand::Reg32(r1), dword_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, dword ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS32, REGS64,
                  |r1, r2| (Operand::Reg32(r1), dword_pointer(r2)),
@@ -1053,6 +1182,11 @@ fn test_reg64_qword_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg64(r1), qword_pointer(0x12345678)),
              |s1| format!("{}, qword ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS64,
+             |r1| (Operand::Reg64(r1), qword_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, qword ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS64,
                  |r1, r2| (Operand::Reg64(r1), qword_pointer(r2)),
@@ -1114,6 +1248,11 @@ fn test_reg16_byte_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg16(r1), byte_pointer(0x12345678)),
              |s1| format!("{}, byte ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS16,
+             |r1| (Operand::Reg16(r1), byte_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, byte ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS16, REGS64,
                  |r1, r2| (Operand::Reg16(r1), byte_pointer(r2)),
@@ -1175,6 +1314,11 @@ fn test_reg32_byte_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg32(r1), byte_pointer(0x12345678)),
              |s1| format!("{}, byte ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS32,
+             |r1| (Operand::Reg32(r1), byte_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, byte ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS32, REGS64,
                  |r1, r2| (Operand::Reg32(r1), byte_pointer(r2)),
@@ -1236,6 +1380,11 @@ fn test_reg64_byte_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg64(r1), byte_pointer(0x12345678)),
              |s1| format!("{}, byte ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS64,
+             |r1| (Operand::Reg64(r1), byte_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, byte ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS64,
                  |r1, r2| (Operand::Reg64(r1), byte_pointer(r2)),
@@ -1297,6 +1446,11 @@ fn test_reg32_word_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg32(r1), word_pointer(0x12345678)),
              |s1| format!("{}, word ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS32,
+             |r1| (Operand::Reg32(r1), word_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, word ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS32, REGS64,
                  |r1, r2| (Operand::Reg32(r1), word_pointer(r2)),
@@ -1358,6 +1512,11 @@ fn test_reg64_word_ptr(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r1| (Operand::Reg64(r1), word_pointer(0x12345678)),
              |s1| format!("{}, word ptr [0x12345678]", s1));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS64,
+             |r1| (Operand::Reg64(r1), word_pointer(Rip + 0x12345678)),
+             |s1| format!("{}, word ptr [rip + 0x12345678]", s1));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS64,
                  |r1, r2| (Operand::Reg64(r1), word_pointer(r2)),
@@ -1421,6 +1580,11 @@ fn test_byte_ptr_imm8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> R
         f(&mut code, byte_pointer(0x12345678), Operand::Imm8(0x78)).unwrap();
         let expected_disasm = vec![Some("byte ptr [0x12345678], 0x78")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, byte_pointer(Rip + 0x12345678), Operand::Imm8(0x78)).unwrap();
+        let expected_disasm = vec![Some("byte ptr [rip + 0x12345678], 0x78")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -1486,6 +1650,11 @@ fn test_byte_ptr_reg8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> R
              |r| (byte_pointer(0x12345678), Operand::Reg8(r)),
              |s| format!("byte ptr [0x12345678], {}", s));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS8,
+             |r| (byte_pointer(Rip + 0x12345678), Operand::Reg8(r)),
+             |s| format!("byte ptr [rip + 0x12345678], {}", s));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  SIMPLE_REGS64, REGS8,
                  |r1, r2| (byte_pointer(r1), Operand::Reg8(r2)),
@@ -1585,6 +1754,11 @@ fn test_word_ptr_imm8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> R
         f(&mut code, word_pointer(0x12345678), Operand::Imm8(0x5a)).unwrap();
         let expected_disasm = vec![Some("word ptr [0x12345678], 0x5a")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, word_pointer(Rip + 0x12345678), Operand::Imm8(0x5a)).unwrap();
+        let expected_disasm = vec![Some("word ptr [rip + 0x12345678], 0x5a")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -1650,6 +1824,11 @@ fn test_word_ptr_imm16(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
         f(&mut code, word_pointer(0x12345678), Operand::Imm16(0x1234)).unwrap();
         let expected_disasm = vec![Some("word ptr [0x12345678], 0x1234")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, word_pointer(Rip + 0x12345678), Operand::Imm16(0x1234)).unwrap();
+        let expected_disasm = vec![Some("word ptr [rip + 0x12345678], 0x1234")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -1713,6 +1892,11 @@ fn test_word_ptr_reg16(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r| (word_pointer(0x12345678), Operand::Reg16(r)),
              |s| format!("word ptr [0x12345678], {}", s));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS16,
+             |r| (word_pointer(Rip + 0x12345678), Operand::Reg16(r)),
+             |s| format!("word ptr [rip + 0x12345678], {}", s));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS16,
                  |r1, r2| (word_pointer(r1), Operand::Reg16(r2)),
@@ -1776,6 +1960,11 @@ fn test_dword_ptr_imm8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
         f(&mut code, dword_pointer(0x12345678), Operand::Imm8(0x5a)).unwrap();
         let expected_disasm = vec![Some("dword ptr [0x12345678], 0x5a")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, dword_pointer(Rip + 0x12345678), Operand::Imm8(0x5a)).unwrap();
+        let expected_disasm = vec![Some("dword ptr [rip + 0x12345678], 0x5a")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -1841,6 +2030,11 @@ fn test_dword_ptr_imm32(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
         f(&mut code, dword_pointer(0x12345678), Operand::Imm32(0x12345678)).unwrap();
         let expected_disasm = vec![Some("dword ptr [0x12345678], 0x12345678")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, dword_pointer(Rip + 0x12345678), Operand::Imm32(0x12345678)).unwrap();
+        let expected_disasm = vec![Some("dword ptr [rip + 0x12345678], 0x12345678")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -1904,6 +2098,11 @@ fn test_dword_ptr_reg32(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r| (dword_pointer(0x12345678), Operand::Reg32(r)),
              |s| format!("dword ptr [0x12345678], {}", s));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS32,
+             |r| (dword_pointer(Rip + 0x12345678), Operand::Reg32(r)),
+             |s| format!("dword ptr [rip + 0x12345678], {}", s));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS32,
                  |r1, r2| (dword_pointer(r1), Operand::Reg32(r2)),
@@ -1967,6 +2166,11 @@ fn test_qword_ptr_imm8(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
         f(&mut code, qword_pointer(0x12345678), Operand::Imm8(0x5a)).unwrap();
         let expected_disasm = vec![Some("qword ptr [0x12345678], 0x5a")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, qword_pointer(Rip + 0x12345678), Operand::Imm8(0x5a)).unwrap();
+        let expected_disasm = vec![Some("qword ptr [rip + 0x12345678], 0x5a")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -2032,6 +2236,11 @@ fn test_qword_ptr_imm32(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
         f(&mut code, qword_pointer(0x12345678), Operand::Imm32(0x12345678)).unwrap();
         let expected_disasm = vec![Some("qword ptr [0x12345678], 0x12345678")];
         test_disasm(mnemonic, &expected_disasm, &code);
+
+        let mut code = Vec::new();
+        f(&mut code, qword_pointer(Rip + 0x12345678), Operand::Imm32(0x12345678)).unwrap();
+        let expected_disasm = vec![Some("qword ptr [rip + 0x12345678], 0x12345678")];
+        test_disasm(mnemonic, &expected_disasm, &code);
     }
 
     test_reg(mnemonic, |v, (m, imm)| f(v, m, imm),
@@ -2095,6 +2304,11 @@ fn test_qword_ptr_reg64(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) ->
              |r| (qword_pointer(0x12345678), Operand::Reg64(r)),
              |s| format!("qword ptr [0x12345678], {}", s));
 
+    test_reg(mnemonic, |v, (r, m)| f(v, r, m),
+             REGS64,
+             |r| (qword_pointer(Rip + 0x12345678), Operand::Reg64(r)),
+             |s| format!("qword ptr [rip + 0x12345678], {}", s));
+
     test_reg_reg(mnemonic, |v, (r, m)| f(v, r, m),
                  REGS64, REGS64,
                  |r1, r2| (qword_pointer(r1), Operand::Reg64(r2)),
@@ -2171,6 +2385,78 @@ fn test_shift_reg64(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Res
 }
 
 
+fn test_xmm_xmm(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Result<()>) {
+    test_reg_reg(mnemonic, |v, (r1, r2)| f(v, r1, r2),
+                 XMMS, XMMS,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+}
+
+fn mem_operands() -> Vec<(Pointer, String)> {
+    let index_regs = REGS64.iter().filter(|&&(r, _)| r != Rsp && r != R12)
+        .cloned().collect::<Vec<_>>();
+
+    let mut mems = vec![
+        (Pointer::from(0x42i8), "0x42".to_string()),
+        (Pointer::from(0x12345678), "0x12345678".to_string()),
+        (Pointer::from(Rip + 0x12345678), "rip + 0x12345678".to_string()),
+    ];
+    for &(base, b) in REGS64.iter() {
+        mems.push((Pointer::from(base), b.to_string()));
+        mems.push((Pointer::from(base + 0x42i8), format!("{} + 0x42", b)));
+        mems.push((Pointer::from(base + 0x12345678), format!("{} + 0x12345678", b)));
+    }
+    for &(index, i) in index_regs.iter() {
+        mems.push((Pointer::from(index*4), format!("{}*4", i)));
+        mems.push((Pointer::from(index*4 + 0x12345678), format!("{}*4 + 0x12345678", i)));
+        for &(base, b) in SIMPLE_REGS64.iter() {
+            mems.push((Pointer::from(base + index*4 + 0x42i8), format!("{} + {}*4 + 0x42", b, i)));
+        }
+    }
+    mems
+}
+
+fn test_xmm_mem(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Result<()>,
+                ptr: fn(Pointer) -> Operand, size: &str)
+{
+    test_reg_mem(mnemonic, f, &XMMS, Operand::Xmm, ptr, size);
+}
+
+fn test_reg_mem<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Result<()>,
+                   regs: &[(R, &'static str)], reg: fn(R) -> Operand,
+                   ptr: fn(Pointer) -> Operand, size: &str)
+    where R: Copy
+{
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for &(r, s) in regs {
+        for &(m, ref ms) in mem_operands().iter() {
+            f(&mut code, reg(r), ptr(m)).unwrap();
+            expected_disasm.push(Some(format!("{}, {} ptr [{}]", s, size, ms)));
+        }
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+fn test_mem_xmm(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Result<()>,
+                ptr: fn(Pointer) -> Operand, size: &str)
+{
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for &(m, ref ms) in mem_operands().iter() {
+        for &(r, s) in XMMS.iter() {
+            f(&mut code, ptr(m), Operand::Xmm(r)).unwrap();
+            expected_disasm.push(Some(format!("{} ptr [{}], {}", size, ms, s)));
+        }
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+
 macro_rules! test_op2 {
     ($mnemonic:expr, $f:path) => {
         test_reg8_imm8($mnemonic, $f);
@@ -2280,6 +2566,35 @@ macro_rules! test_op1 {
 }
 
 
+macro_rules! test_lock_op2 {
+    ($mnemonic:expr, $f:path) => {
+        test_byte_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_word_ptr_imm16($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_dword_ptr_imm32($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_qword_ptr_imm32($mnemonic, |v, d, s| $f(v, Lock(d), s));
+
+        test_word_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_dword_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_qword_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+
+        test_byte_ptr_reg8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_word_ptr_reg16($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_dword_ptr_reg32($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_qword_ptr_reg64($mnemonic, |v, d, s| $f(v, Lock(d), s));
+    }
+}
+
+
+macro_rules! test_lock_op1 {
+    ($mnemonic:expr, $f:path) => {
+        test_byte_ptr($mnemonic, |v, a| $f(v, Lock(a)));
+        test_word_ptr($mnemonic, |v, a| $f(v, Lock(a)));
+        test_dword_ptr($mnemonic, |v, a| $f(v, Lock(a)));
+        test_qword_ptr($mnemonic, |v, a| $f(v, Lock(a)));
+    }
+}
+
+
 #[test]
 fn test_add() {
     test_op2!("add", Emit::emit_add);
@@ -2320,11 +2635,37 @@ fn test_cmp() {
     test_op2!("cmp", Emit::emit_cmp);
 }
 
+#[test]
+fn test_rol() {
+    test_shift!("rol", Emit::emit_rol);
+}
+
+#[test]
+fn test_ror() {
+    test_shift!("ror", Emit::emit_ror);
+}
+
+#[test]
+fn test_rcl() {
+    test_shift!("rcl", Emit::emit_rcl);
+}
+
+#[test]
+fn test_rcr() {
+    test_shift!("rcr", Emit::emit_rcr);
+}
+
 #[test]
 fn test_shl() {
     test_shift!("shl", Emit::emit_shl);
 }
 
+#[test]
+fn test_sal() {
+    // sal is an alias of shl with the same encoding
+    test_shift!("shl", Emit::emit_sal);
+}
+
 #[test]
 fn test_shr() {
     test_shift!("shr", Emit::emit_shr);
@@ -2335,6 +2676,53 @@ fn test_sar() {
     test_shift!("sar", Emit::emit_sar);
 }
 
+fn test_double_shift<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>,
+                        regs: &[(R, &'static str)], reg: fn(R) -> Operand,
+                        ptr: fn(Pointer) -> Operand, size: &str)
+    where R: Copy
+{
+    test_reg_reg(mnemonic, |v, (r1, r2)| f(v, r1, r2, Operand::Imm8(0x12)),
+                 regs, regs,
+                 |r1, r2| (reg(r1), reg(r2)),
+                 |s1, s2| format!("{}, {}, 0x12", s1, s2));
+    test_reg_reg(mnemonic, |v, (r1, r2)| f(v, r1, r2, Operand::Reg8(Cl)),
+                 regs, regs,
+                 |r1, r2| (reg(r1), reg(r2)),
+                 |s1, s2| format!("{}, {}, cl", s1, s2));
+
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for &(r, s) in regs.iter() {
+        for &(m, ref ms) in mem_operands().iter() {
+            f(&mut code, ptr(m), reg(r), Operand::Imm8(0x12)).unwrap();
+            expected_disasm.push(Some(format!("{} ptr [{}], {}, 0x12", size, ms, s)));
+            f(&mut code, ptr(m), reg(r), Operand::Reg8(Cl)).unwrap();
+            expected_disasm.push(Some(format!("{} ptr [{}], {}, cl", size, ms, s)));
+        }
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+macro_rules! test_double_shift {
+    ($mnemonic:expr, $f:path) => {
+        test_double_shift($mnemonic, $f, &REGS16, Operand::Reg16, word_pointer, "word");
+        test_double_shift($mnemonic, $f, &REGS32, Operand::Reg32, dword_pointer, "dword");
+        test_double_shift($mnemonic, $f, &REGS64, Operand::Reg64, qword_pointer, "qword");
+    }
+}
+
+#[test]
+fn test_shld() {
+    test_double_shift!("shld", Emit::emit_shld);
+}
+
+#[test]
+fn test_shrd() {
+    test_double_shift!("shrd", Emit::emit_shrd);
+}
+
 #[test]
 fn test_not() {
     test_op1!("not", Emit::emit_not);
@@ -2355,6 +2743,53 @@ fn test_imul() {
     test_op1!("imul", Emit::emit_imul);
 }
 
+#[test]
+fn test_imul2() {
+    test_reg16_reg16("imul", Emit::emit_imul2);
+    test_reg32_reg32("imul", Emit::emit_imul2);
+    test_reg64_reg64("imul", Emit::emit_imul2);
+
+    test_reg16_word_ptr("imul", Emit::emit_imul2);
+    test_reg32_dword_ptr("imul", Emit::emit_imul2);
+    test_reg64_qword_ptr("imul", Emit::emit_imul2);
+}
+
+fn test_imul3<R>(regs: &[(R, &'static str)], reg: fn(R) -> Operand,
+                 ptr: fn(Pointer) -> Operand, size: &str, imm: Operand, imm_str: &str)
+    where R: Copy
+{
+    test_reg_reg("imul", |v, (r1, r2)| Emit::emit_imul3(v, r1, r2, imm),
+                 regs, regs,
+                 |r1, r2| (reg(r1), reg(r2)),
+                 |s1, s2| format!("{}, {}, {}", s1, s2, imm_str));
+
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for &(r, s) in regs.iter() {
+        for &(m, ref ms) in mem_operands().iter() {
+            code.emit_imul3(reg(r), ptr(m), imm).unwrap();
+            expected_disasm.push(Some(format!("{}, {} ptr [{}], {}", s, size, ms, imm_str)));
+        }
+    }
+
+    test_disasm("imul", &expected_disasm, &code);
+}
+
+#[test]
+fn test_imul3_imm8() {
+    test_imul3(&REGS16, Operand::Reg16, word_pointer, "word", Operand::Imm8(0x12), "0x12");
+    test_imul3(&REGS32, Operand::Reg32, dword_pointer, "dword", Operand::Imm8(0x12), "0x12");
+    test_imul3(&REGS64, Operand::Reg64, qword_pointer, "qword", Operand::Imm8(0x12), "0x12");
+}
+
+#[test]
+fn test_imul3_imm() {
+    test_imul3(&REGS16, Operand::Reg16, word_pointer, "word", Operand::Imm16(0x1234), "0x1234");
+    test_imul3(&REGS32, Operand::Reg32, dword_pointer, "dword", Operand::Imm32(0x12345678), "0x12345678");
+    test_imul3(&REGS64, Operand::Reg64, qword_pointer, "qword", Operand::Imm32(0x12345678), "0x12345678");
+}
+
 #[test]
 fn test_div() {
     test_op1!("div", Emit::emit_div);
@@ -2452,6 +2887,7 @@ fn test_pop() {
 fn test_call() {
     test_off32("call", Emit::emit_call);
     test_reg64("call", Emit::emit_call);
+    test_qword_ptr("call", Emit::emit_call);
 }
 
 #[test]
@@ -2459,6 +2895,57 @@ fn test_jmp() {
     test_off8("jmp", Emit::emit_jmp);
     test_off32("jmp", Emit::emit_jmp);
     test_reg64("jmp", Emit::emit_jmp);
+    test_qword_ptr("jmp", Emit::emit_jmp);
+}
+
+#[test]
+fn test_jmp_table() {
+    let mut code = Vec::new();
+    code.emit_jmp(qword_ptr(Rax + Rcx * 8)).unwrap();
+    code.emit_call(qword_ptr(Rdi + 0x10i8)).unwrap();
+    let capstone = Capstone::new().x86().mode(ArchMode::Mode64).build().unwrap();
+    let disasm = capstone.disasm_all(&code, 0x0).unwrap();
+    let disasm = disasm.iter()
+        .map(|i| format!("{} {}", i.mnemonic().unwrap(), i.op_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(disasm, ["jmp qword ptr [rax + rcx*8]", "call qword ptr [rdi + 0x10]"]);
+}
+
+fn disasm_at(code: &[u8], addr: u64) -> Vec<String> {
+    let capstone = Capstone::new().x86().mode(ArchMode::Mode64).build().unwrap();
+    let disasm = capstone.disasm_all(code, addr).unwrap();
+    disasm.iter()
+        .map(|i| format!("{} {}", i.mnemonic().unwrap(), i.op_str().unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_call_abs() {
+    let mut code = Vec::new();
+    code.emit_call_abs(0x123456789a, Rax).unwrap();
+    code.emit_jmp_abs(0x42, R11).unwrap();
+    assert_eq!(disasm_at(&code, 0), [
+        "movabs rax, 0x123456789a", "call rax",
+        "movabs r11, 0x42", "jmp r11",
+    ]);
+
+    let base = 0x7fff_1234_0000;
+    let mut code = Based::new(base, Vec::new());
+    code.emit_call_abs(base + 0x1000, Rax).unwrap();
+    code.emit_jmp_abs(base.wrapping_sub(0x1000), Rax).unwrap();
+    code.emit_call_abs(base + 0x1_0000_0000, Rax).unwrap();
+    assert_eq!(disasm_at(code.get_ref(), base), [
+        format!("call 0x{:x}", base + 0x1000),
+        format!("jmp 0x{:x}", base.wrapping_sub(0x1000)),
+        format!("movabs rax, 0x{:x}", base + 0x1_0000_0000),
+        "call rax".to_string(),
+    ]);
+
+    let mut buffer = [0; 16];
+    let mut code = Cursor::new(&mut buffer[..]);
+    code.emit_call_abs(0x42, Rax).unwrap();
+    let len = code.position() as usize;
+    assert_eq!(disasm_at(&buffer[..len], 0), ["movabs rax, 0x42", "call rax"]);
 }
 
 #[test]
@@ -2466,6 +2953,195 @@ fn test_ret() {
     test_unit("ret", Emit::emit_ret);
 }
 
+#[test]
+fn test_ret_imm() {
+    let mut code = Vec::new();
+    code.emit_ret_imm(0x1234u16).unwrap();
+    code.emit_ret_imm(Operand::Imm16(0x10)).unwrap();
+    test_disasm("ret", &[Some("0x1234"), Some("0x10")], &code);
+
+    assert!(matches!(code.emit_ret_imm(Operand::Imm8(0x10)), Err(Error::InvalidOperands)));
+}
+
+#[test]
+fn test_leave() {
+    test_unit("leave", Emit::emit_leave);
+}
+
+#[test]
+fn test_enter() {
+    let mut code = Vec::new();
+    code.emit_enter(0x1234u16, 0u8).unwrap();
+    code.emit_enter(Operand::Imm16(0x20), Operand::Imm8(3)).unwrap();
+    test_disasm("enter", &[Some("0x1234, 0"), Some("0x20, 3")], &code);
+}
+
+#[test]
+fn test_label() {
+    let mut buffer = [0u8; 256];
+    let len = {
+        let mut code = Cursor::new(&mut buffer[..]);
+        let mut head = OptionLabel::new();
+        let mut exit = OptionLabel::new();
+        code.bind_label(&mut head).unwrap();
+        code.emit_ud2().unwrap();
+        code.emit_jne(&mut head).unwrap();
+        code.emit_jmp(&mut exit).unwrap();
+        code.emit_jmp(&mut head).unwrap();
+        for _ in 0..13 {
+            code.emit_mov(Rax, 0x1234567812345678u64).unwrap();
+        }
+        code.emit_jmp(&mut head).unwrap();
+        code.emit_je(&mut head).unwrap();
+        code.bind_label(&mut exit).unwrap();
+        code.position() as usize
+    };
+    assert_eq!(&buffer[0..11], &[0x0f, 0x0b, 0x75, 0xfc, 0xe9, 0x8f, 0x00, 0x00, 0x00, 0xeb, 0xf5]);
+    assert_eq!(&buffer[141..len], &[0xe9, 0x6e, 0xff, 0xff, 0xff, 0x0f, 0x84, 0x68, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn test_short_label() {
+    let mut buffer = [0u8; 8];
+    {
+        let mut code = Cursor::new(&mut buffer[..]);
+        let mut head = OptionLabel::new();
+        let mut exit = OptionLabel::new();
+        code.bind_label(&mut head).unwrap();
+        code.emit_jz_short(&mut exit).unwrap();
+        code.emit_ud2().unwrap();
+        code.emit_jmp_short(&mut head).unwrap();
+        code.bind_label(&mut exit).unwrap();
+    }
+    assert_eq!(&buffer[0..6], &[0x74, 0x04, 0x0f, 0x0b, 0xeb, 0xfa]);
+
+    let mut buffer = [0u8; 256];
+    let mut code = Cursor::new(&mut buffer[..]);
+    let mut head = OptionLabel::new();
+    code.bind_label(&mut head).unwrap();
+    for _ in 0..13 {
+        code.emit_mov(Rax, 0x1234567812345678u64).unwrap();
+    }
+    match code.emit_jmp_short(&mut head) {
+        Err(Error::BranchOutOfRange { offset: -132 }) => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let mut buffer = [0u8; 256];
+    let mut code = Cursor::new(&mut buffer[..]);
+    let mut exit = OptionLabel::new();
+    code.emit_jmp_short(&mut exit).unwrap();
+    for _ in 0..13 {
+        code.emit_mov(Rax, 0x1234567812345678u64).unwrap();
+    }
+    match fixup::bind_label(&mut code, &mut exit) {
+        Err(Error::BranchOutOfRange { offset: 130 }) => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_relax() {
+    let mut relax = Relax::new();
+    let head = relax.new_label();
+    let exit = relax.new_label();
+    let far = relax.new_label();
+    relax.bind_label(head).unwrap();
+    relax.emit_dec(Ecx).unwrap();
+    relax.emit_je(exit).unwrap();
+    relax.emit_jmp(head).unwrap();
+    relax.bind_label(exit).unwrap();
+    relax.emit_jmp(far).unwrap();
+    for _ in 0..16 {
+        relax.emit_mov(Rax, 0x1234567812345678u64).unwrap();
+    }
+    relax.bind_label(far).unwrap();
+    relax.emit_ud2().unwrap();
+    relax.emit_call(head).unwrap();
+
+    let mut code = Vec::new();
+    relax.finish(&mut code).unwrap();
+    assert_eq!(code.len(), 178);
+    assert_eq!(&code[0..6], &[0xff, 0xc9, 0x74, 0x02, 0xeb, 0xfa]);
+    test_disasm("jmp", &[Some("0xa5")], &code[6..11]);
+    assert_eq!(&code[171..178], &[0x0f, 0x0b, 0xe8, 0x4e, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn test_relax_label_errors() {
+    let mut relax = Relax::new();
+    let head = relax.new_label();
+    let exit = relax.new_label();
+    relax.bind_label(head).unwrap();
+    match relax.bind_label(head) {
+        Err(Error::LabelAlreadyBound(label)) if label == head => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    relax.emit_jmp(exit).unwrap();
+    let mut code = Vec::new();
+    match relax.finish(&mut code) {
+        Err(Error::UnboundLabel(label)) if label == exit => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+    assert!(code.is_empty());
+
+    let mut other = Relax::new();
+    match other.bind_label(exit) {
+        Err(Error::InvalidLabel(label)) if label == exit => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+    match other.emit_jmp(exit) {
+        Err(Error::InvalidLabel(label)) if label == exit => (),
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn test_relax_pc_relative() {
+    let mut relax = Relax::new();
+    relax.emit_lea(Rax, qword_ptr(Rip + 0x100)).unwrap();
+    let mut code = Vec::new();
+    relax.finish(&mut code).unwrap();
+
+    // raw bytes and instructions without PC-relative operands can be moved
+    let mut relax = Relax::new();
+    let exit = relax.new_label();
+    relax.emit_jne(exit).unwrap();
+    relax.emit_mov(Eax, 5u32).unwrap();
+    relax.emit_add(Eax, 5u32).unwrap();
+    relax.emit_add(Rax, qword_ptr(Rax + 0x5i8)).unwrap();
+    relax.emit_lea(Rax, qword_ptr(Rbp + 0x5i8)).unwrap();
+    relax.emit_shl(Eax, 5u8).unwrap();
+    relax.emit_push(5u8).unwrap();
+    mitte_core::EmitSlice::emit_slice(&mut relax, &[0xe8, 0x05, 0x00, 0x00, 0x00, 0x48, 0x8d, 0x05]).unwrap();
+    relax.bind_label(exit).unwrap();
+    let mut code = Vec::new();
+    relax.finish(&mut code).unwrap();
+    assert_eq!(&code[0..2], &[0x75, 0x1f]);
+
+    let pc_relative: &[fn(&mut Relax) -> Result<()>] = &[
+        |r| r.emit_lea(Rax, qword_ptr(Rip + 0x100)),
+        |r| r.emit_mov(dword_pointer(Rip + 0x100), Operand::Imm32(1)),
+        |r| r.emit_jmp(0x10i32),
+        |r| r.emit_je(0x10i8),
+        |r| r.emit_call(0x10i32),
+    ];
+    for (i, f) in pc_relative.iter().enumerate() {
+        let mut relax = Relax::new();
+        let exit = relax.new_label();
+        relax.emit_jne(exit).unwrap();
+        relax.emit_inc(Ecx).unwrap();
+        f(&mut relax).unwrap();
+        relax.bind_label(exit).unwrap();
+        let mut code = Vec::new();
+        match relax.finish(&mut code) {
+            Err(Error::PcRelative { pos: 2 }) => (),
+            r => panic!("unexpected result {:?} for instruction {}", r, i),
+        }
+    }
+}
+
 test_cmovcc! {
     (test_cmova,   "cmova",  Emit::emit_cmova),
     (test_cmovae,  "cmovae", Emit::emit_cmovae),
@@ -2499,9 +3175,49 @@ test_cmovcc! {
     (test_cmovz,   "cmove",  Emit::emit_cmovz),
 }
 
-test_jcc! {
-    (test_ja,   "ja",  Emit::emit_ja),
-    (test_jae,  "jae", Emit::emit_jae),
+const CONDS: [Cond; 16] = [
+    cond::O, cond::NO, cond::B, cond::AE, cond::E, cond::NE, cond::BE, cond::A,
+    cond::S, cond::NS, cond::P, cond::NP, cond::L, cond::GE, cond::LE, cond::G,
+];
+
+#[test]
+fn test_cond() {
+    for &cond in CONDS.iter() {
+        let mut code = Vec::new();
+        code.emit_jcc(cond, Operand::Offset32(0x12345678)).unwrap();
+        test_disasm(&format!("j{}", cond), &[Some("0x12345678")], &code);
+
+        let mut code = Vec::new();
+        code.emit_setcc(cond, Operand::Reg8(Cl)).unwrap();
+        test_disasm(&format!("set{}", cond), &[Some("cl")], &code);
+
+        let mut code = Vec::new();
+        code.emit_cmovcc(cond, R8, Rcx).unwrap();
+        test_disasm(&format!("cmov{}", cond), &[Some("r8, rcx")], &code);
+
+        assert_ne!(cond.negate(), cond);
+        assert_eq!(cond.negate().negate(), cond);
+        if let Some(swapped) = cond.swap_operands() {
+            assert_eq!(swapped.swap_operands(), Some(cond));
+            assert_eq!(swapped.is_signed(), cond.is_signed());
+            assert_eq!(swapped.is_unsigned(), cond.is_unsigned());
+        }
+        assert!(!(cond.is_signed() && cond.is_unsigned()));
+    }
+
+    assert_eq!(cond::L.negate(), cond::GE);
+    assert_eq!(cond::L.swap_operands(), Some(cond::G));
+    assert_eq!(cond::AE.swap_operands(), Some(cond::BE));
+    assert_eq!(cond::E.swap_operands(), Some(cond::E));
+    assert_eq!(cond::S.swap_operands(), None);
+    assert!(cond::LE.is_signed());
+    assert!(cond::B.is_unsigned());
+    assert!(!cond::E.is_signed() && !cond::E.is_unsigned());
+}
+
+test_jcc! {
+    (test_ja,   "ja",  Emit::emit_ja),
+    (test_jae,  "jae", Emit::emit_jae),
     (test_jb,   "jb",  Emit::emit_jb),
     (test_jbe,  "jbe", Emit::emit_jbe),
     (test_jc,   "jb",  Emit::emit_jc),
@@ -2572,6 +3288,23 @@ fn test_lea() {
     test_reg64_qword_ptr("lea", Emit::emit_lea);
 }
 
+#[test]
+fn test_rip_label() {
+    let mut buffer = [0u8; 32];
+    {
+        let mut code = Cursor::new(&mut buffer[..]);
+        let mut label = OptionLabel::new();
+        code.emit_lea(Rax, qword_ptr(Rip + &mut label)).unwrap();
+        code.emit_mov(dword_ptr(Rip + &mut label), 0x12345678u32).unwrap();
+        code.emit_ud2().unwrap();
+        code.bind_label(&mut label).unwrap();
+        code.emit_mov(R8, qword_ptr(Rip + &mut label)).unwrap();
+    }
+    test_disasm("lea", &[Some("rax, [rip + 0xc]")], &buffer[0..7]);
+    test_disasm("mov", &[Some("dword ptr [rip + 2], 0x12345678")], &buffer[7..17]);
+    test_disasm("mov", &[Some("r8, qword ptr [rip - 7]")], &buffer[19..26]);
+}
+
 #[test]
 fn test_movzx() {
     test_reg16_reg8("movzx", Emit::emit_movzx);
@@ -2628,11 +3361,91 @@ fn test_bsr() {
     test_reg64_qword_ptr("bsr", Emit::emit_bsr);
 }
 
+macro_rules! test_bit_test {
+    ($mnemonic:expr, $f:path) => {
+        test_reg16_reg16($mnemonic, $f);
+        test_reg32_reg32($mnemonic, $f);
+        test_reg64_reg64($mnemonic, $f);
+
+        test_reg16_imm8($mnemonic, $f);
+        test_reg32_imm8($mnemonic, $f);
+        test_reg64_imm8($mnemonic, $f);
+
+        test_word_ptr_reg16($mnemonic, $f);
+        test_dword_ptr_reg32($mnemonic, $f);
+        test_qword_ptr_reg64($mnemonic, $f);
+
+        test_word_ptr_imm8($mnemonic, $f);
+        test_dword_ptr_imm8($mnemonic, $f);
+        test_qword_ptr_imm8($mnemonic, $f);
+    }
+}
+
+macro_rules! test_lock_bit_test {
+    ($mnemonic:expr, $f:path) => {
+        test_word_ptr_reg16($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_dword_ptr_reg32($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_qword_ptr_reg64($mnemonic, |v, d, s| $f(v, Lock(d), s));
+
+        test_word_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_dword_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+        test_qword_ptr_imm8($mnemonic, |v, d, s| $f(v, Lock(d), s));
+    }
+}
+
+#[test]
+fn test_bt() {
+    test_bit_test!("bt", Emit::emit_bt);
+}
+
+#[test]
+fn test_bts() {
+    test_bit_test!("bts", Emit::emit_bts);
+    test_lock_bit_test!("lock bts", Emit::emit_bts);
+}
+
+#[test]
+fn test_btr() {
+    test_bit_test!("btr", Emit::emit_btr);
+    test_lock_bit_test!("lock btr", Emit::emit_btr);
+}
+
+#[test]
+fn test_btc() {
+    test_bit_test!("btc", Emit::emit_btc);
+    test_lock_bit_test!("lock btc", Emit::emit_btc);
+}
+
+#[test]
+fn test_cbw() {
+    test_unit("cbw", Emit::emit_cbw);
+}
+
+#[test]
+fn test_cwde() {
+    test_unit("cwde", Emit::emit_cwde);
+}
+
+#[test]
+fn test_cdqe() {
+    test_unit("cdqe", Emit::emit_cdqe);
+}
+
+#[test]
+fn test_cwd() {
+    test_unit("cwd", Emit::emit_cwd);
+}
+
 #[test]
 fn test_cdq() {
     test_unit("cdq", Emit::emit_cdq);
 }
 
+#[test]
+fn test_cqo() {
+    test_unit("cqo", Emit::emit_cqo);
+}
+
 #[test]
 fn test_xchg() {
     test_reg8_reg8("xchg", Emit::emit_xchg);
@@ -2692,3 +3505,959 @@ fn test_xchg() {
 fn test_ud2() {
     test_unit("ud2", Emit::emit_ud2);
 }
+
+#[test]
+fn test_lfence() {
+    test_unit("lfence", Emit::emit_lfence);
+}
+
+#[test]
+fn test_mfence() {
+    test_unit("mfence", Emit::emit_mfence);
+}
+
+#[test]
+fn test_sfence() {
+    test_unit("sfence", Emit::emit_sfence);
+}
+
+#[test]
+fn test_pause() {
+    test_unit("pause", Emit::emit_pause);
+}
+
+#[test]
+fn test_clflush() {
+    test_byte_ptr("clflush", Emit::emit_clflush);
+}
+
+#[test]
+fn test_clflushopt() {
+    test_byte_ptr("clflushopt", Emit::emit_clflushopt);
+}
+
+#[test]
+fn test_clwb() {
+    test_byte_ptr("clwb", Emit::emit_clwb);
+}
+
+#[test]
+fn test_movss() {
+    test_xmm_xmm("movss", Emit::emit_movss);
+    test_xmm_mem("movss", Emit::emit_movss, dword_pointer, "dword");
+    test_mem_xmm("movss", Emit::emit_movss, dword_pointer, "dword");
+}
+
+#[test]
+fn test_movsd() {
+    test_xmm_xmm("movsd", Emit::emit_movsd);
+    test_xmm_mem("movsd", Emit::emit_movsd, qword_pointer, "qword");
+    test_mem_xmm("movsd", Emit::emit_movsd, qword_pointer, "qword");
+}
+
+#[test]
+fn test_movd() {
+    test_reg_reg("movd", |v, (r1, r2)| Emit::emit_movd(v, r1, r2),
+                 XMMS, REGS32,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg32(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("movd", |v, (r1, r2)| Emit::emit_movd(v, r1, r2),
+                 REGS32, XMMS,
+                 |r1, r2| (Operand::Reg32(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_xmm_mem("movd", Emit::emit_movd, dword_pointer, "dword");
+    test_mem_xmm("movd", Emit::emit_movd, dword_pointer, "dword");
+}
+
+#[test]
+fn test_movq() {
+    test_xmm_xmm("movq", Emit::emit_movq);
+    test_reg_reg("movq", |v, (r1, r2)| Emit::emit_movq(v, r1, r2),
+                 XMMS, REGS64,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg64(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("movq", |v, (r1, r2)| Emit::emit_movq(v, r1, r2),
+                 REGS64, XMMS,
+                 |r1, r2| (Operand::Reg64(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_xmm_mem("movq", Emit::emit_movq, qword_pointer, "qword");
+    test_mem_xmm("movq", Emit::emit_movq, qword_pointer, "qword");
+}
+
+macro_rules! test_sse_scalar {
+    ($( ($test:ident, $mnemonic:expr, $f:path, $ptr:ident, $size:expr), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_xmm_xmm($mnemonic, $f);
+                test_xmm_mem($mnemonic, $f, $ptr, $size);
+            }
+        )*
+    }
+}
+
+test_sse_scalar! {
+    (test_addss,   "addss",   Emit::emit_addss,   dword_pointer, "dword"),
+    (test_addsd,   "addsd",   Emit::emit_addsd,   qword_pointer, "qword"),
+    (test_subss,   "subss",   Emit::emit_subss,   dword_pointer, "dword"),
+    (test_subsd,   "subsd",   Emit::emit_subsd,   qword_pointer, "qword"),
+    (test_mulss,   "mulss",   Emit::emit_mulss,   dword_pointer, "dword"),
+    (test_mulsd,   "mulsd",   Emit::emit_mulsd,   qword_pointer, "qword"),
+    (test_divss,   "divss",   Emit::emit_divss,   dword_pointer, "dword"),
+    (test_divsd,   "divsd",   Emit::emit_divsd,   qword_pointer, "qword"),
+    (test_sqrtss,  "sqrtss",  Emit::emit_sqrtss,  dword_pointer, "dword"),
+    (test_sqrtsd,  "sqrtsd",  Emit::emit_sqrtsd,  qword_pointer, "qword"),
+    (test_minss,   "minss",   Emit::emit_minss,   dword_pointer, "dword"),
+    (test_minsd,   "minsd",   Emit::emit_minsd,   qword_pointer, "qword"),
+    (test_maxss,   "maxss",   Emit::emit_maxss,   dword_pointer, "dword"),
+    (test_maxsd,   "maxsd",   Emit::emit_maxsd,   qword_pointer, "qword"),
+    (test_ucomiss, "ucomiss", Emit::emit_ucomiss, dword_pointer, "dword"),
+    (test_ucomisd, "ucomisd", Emit::emit_ucomisd, qword_pointer, "qword"),
+    (test_comiss,  "comiss",  Emit::emit_comiss,  dword_pointer, "dword"),
+    // capstone prints the m64 operand of comisd as xmmword
+    (test_comisd,  "comisd",  Emit::emit_comisd,  qword_pointer, "xmmword"),
+    (test_cvtss2sd, "cvtss2sd", Emit::emit_cvtss2sd, dword_pointer, "dword"),
+    (test_cvtsd2ss, "cvtsd2ss", Emit::emit_cvtsd2ss, qword_pointer, "qword"),
+}
+
+#[test]
+fn test_cvtsi2ss() {
+    test_reg_reg("cvtsi2ss", |v, (r1, r2)| Emit::emit_cvtsi2ss(v, r1, r2),
+                 XMMS, REGS32,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg32(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("cvtsi2ss", |v, (r1, r2)| Emit::emit_cvtsi2ss(v, r1, r2),
+                 XMMS, REGS64,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg64(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_xmm_mem("cvtsi2ss", Emit::emit_cvtsi2ss, dword_pointer, "dword");
+    test_xmm_mem("cvtsi2ss", Emit::emit_cvtsi2ss, qword_pointer, "qword");
+}
+
+#[test]
+fn test_cvtsi2sd() {
+    test_reg_reg("cvtsi2sd", |v, (r1, r2)| Emit::emit_cvtsi2sd(v, r1, r2),
+                 XMMS, REGS32,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg32(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("cvtsi2sd", |v, (r1, r2)| Emit::emit_cvtsi2sd(v, r1, r2),
+                 XMMS, REGS64,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Reg64(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_xmm_mem("cvtsi2sd", Emit::emit_cvtsi2sd, dword_pointer, "dword");
+    test_xmm_mem("cvtsi2sd", Emit::emit_cvtsi2sd, qword_pointer, "qword");
+}
+
+macro_rules! test_cvt_float_to_int {
+    ($( ($test:ident, $mnemonic:expr, $f:path, $ptr:ident, $size:expr), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_reg_reg($mnemonic, |v, (r1, r2)| $f(v, r1, r2),
+                             REGS32, XMMS,
+                             |r1, r2| (Operand::Reg32(r1), Operand::Xmm(r2)),
+                             |s1, s2| format!("{}, {}", s1, s2));
+                test_reg_reg($mnemonic, |v, (r1, r2)| $f(v, r1, r2),
+                             REGS64, XMMS,
+                             |r1, r2| (Operand::Reg64(r1), Operand::Xmm(r2)),
+                             |s1, s2| format!("{}, {}", s1, s2));
+                test_reg_mem($mnemonic, $f, &REGS32, Operand::Reg32, $ptr, $size);
+                test_reg_mem($mnemonic, $f, &REGS64, Operand::Reg64, $ptr, $size);
+            }
+        )*
+    }
+}
+
+test_cvt_float_to_int! {
+    (test_cvtss2si,  "cvtss2si",  Emit::emit_cvtss2si,  dword_pointer, "dword"),
+    (test_cvtsd2si,  "cvtsd2si",  Emit::emit_cvtsd2si,  qword_pointer, "qword"),
+    (test_cvttss2si, "cvttss2si", Emit::emit_cvttss2si, dword_pointer, "dword"),
+    (test_cvttsd2si, "cvttsd2si", Emit::emit_cvttsd2si, qword_pointer, "qword"),
+}
+
+#[test]
+fn test_movaps() {
+    test_xmm_xmm("movaps", Emit::emit_movaps);
+    test_xmm_mem("movaps", Emit::emit_movaps, xmmword_pointer, "xmmword");
+    test_mem_xmm("movaps", Emit::emit_movaps, xmmword_pointer, "xmmword");
+}
+
+#[test]
+fn test_movups() {
+    test_xmm_xmm("movups", Emit::emit_movups);
+    test_xmm_mem("movups", Emit::emit_movups, xmmword_pointer, "xmmword");
+    test_mem_xmm("movups", Emit::emit_movups, xmmword_pointer, "xmmword");
+}
+
+#[test]
+fn test_movdqa() {
+    test_xmm_xmm("movdqa", Emit::emit_movdqa);
+    test_xmm_mem("movdqa", Emit::emit_movdqa, xmmword_pointer, "xmmword");
+    test_mem_xmm("movdqa", Emit::emit_movdqa, xmmword_pointer, "xmmword");
+}
+
+#[test]
+fn test_movdqu() {
+    test_xmm_xmm("movdqu", Emit::emit_movdqu);
+    test_xmm_mem("movdqu", Emit::emit_movdqu, xmmword_pointer, "xmmword");
+    test_mem_xmm("movdqu", Emit::emit_movdqu, xmmword_pointer, "xmmword");
+}
+
+test_sse_scalar! {
+    (test_addps,      "addps",      Emit::emit_addps,      xmmword_pointer, "xmmword"),
+    (test_addpd,      "addpd",      Emit::emit_addpd,      xmmword_pointer, "xmmword"),
+    (test_mulps,      "mulps",      Emit::emit_mulps,      xmmword_pointer, "xmmword"),
+    (test_mulpd,      "mulpd",      Emit::emit_mulpd,      xmmword_pointer, "xmmword"),
+    (test_andps,      "andps",      Emit::emit_andps,      xmmword_pointer, "xmmword"),
+    (test_xorps,      "xorps",      Emit::emit_xorps,      xmmword_pointer, "xmmword"),
+    (test_paddb,      "paddb",      Emit::emit_paddb,      xmmword_pointer, "xmmword"),
+    (test_paddw,      "paddw",      Emit::emit_paddw,      xmmword_pointer, "xmmword"),
+    (test_paddd,      "paddd",      Emit::emit_paddd,      xmmword_pointer, "xmmword"),
+    (test_paddq,      "paddq",      Emit::emit_paddq,      xmmword_pointer, "xmmword"),
+    (test_psubb,      "psubb",      Emit::emit_psubb,      xmmword_pointer, "xmmword"),
+    (test_psubw,      "psubw",      Emit::emit_psubw,      xmmword_pointer, "xmmword"),
+    (test_psubd,      "psubd",      Emit::emit_psubd,      xmmword_pointer, "xmmword"),
+    (test_psubq,      "psubq",      Emit::emit_psubq,      xmmword_pointer, "xmmword"),
+    (test_pand,       "pand",       Emit::emit_pand,       xmmword_pointer, "xmmword"),
+    (test_pandn,      "pandn",      Emit::emit_pandn,      xmmword_pointer, "xmmword"),
+    (test_por,        "por",        Emit::emit_por,        xmmword_pointer, "xmmword"),
+    (test_pxor,       "pxor",       Emit::emit_pxor,       xmmword_pointer, "xmmword"),
+    (test_pcmpeqb,    "pcmpeqb",    Emit::emit_pcmpeqb,    xmmword_pointer, "xmmword"),
+    (test_pcmpeqw,    "pcmpeqw",    Emit::emit_pcmpeqw,    xmmword_pointer, "xmmword"),
+    (test_pcmpeqd,    "pcmpeqd",    Emit::emit_pcmpeqd,    xmmword_pointer, "xmmword"),
+    (test_pcmpgtb,    "pcmpgtb",    Emit::emit_pcmpgtb,    xmmword_pointer, "xmmword"),
+    (test_pcmpgtw,    "pcmpgtw",    Emit::emit_pcmpgtw,    xmmword_pointer, "xmmword"),
+    (test_pcmpgtd,    "pcmpgtd",    Emit::emit_pcmpgtd,    xmmword_pointer, "xmmword"),
+    (test_punpcklbw,  "punpcklbw",  Emit::emit_punpcklbw,  xmmword_pointer, "xmmword"),
+    (test_punpcklwd,  "punpcklwd",  Emit::emit_punpcklwd,  xmmword_pointer, "xmmword"),
+    (test_punpckldq,  "punpckldq",  Emit::emit_punpckldq,  xmmword_pointer, "xmmword"),
+    (test_punpcklqdq, "punpcklqdq", Emit::emit_punpcklqdq, xmmword_pointer, "xmmword"),
+    (test_punpckhbw,  "punpckhbw",  Emit::emit_punpckhbw,  xmmword_pointer, "xmmword"),
+    (test_punpckhwd,  "punpckhwd",  Emit::emit_punpckhwd,  xmmword_pointer, "xmmword"),
+    (test_punpckhdq,  "punpckhdq",  Emit::emit_punpckhdq,  xmmword_pointer, "xmmword"),
+    (test_punpckhqdq, "punpckhqdq", Emit::emit_punpckhqdq, xmmword_pointer, "xmmword"),
+}
+
+macro_rules! test_sse_shift {
+    ($( ($test:ident, $mnemonic:expr, $f:path), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_reg($mnemonic, |v, r| $f(v, r, Operand::Imm8(0x42)),
+                         XMMS, |r| Operand::Xmm(r), |s| format!("{}, 0x42", s));
+                test_xmm_xmm($mnemonic, $f);
+                test_xmm_mem($mnemonic, $f, xmmword_pointer, "xmmword");
+            }
+        )*
+    }
+}
+
+test_sse_shift! {
+    (test_psllw, "psllw", Emit::emit_psllw),
+    (test_pslld, "pslld", Emit::emit_pslld),
+    (test_psllq, "psllq", Emit::emit_psllq),
+    (test_psrlw, "psrlw", Emit::emit_psrlw),
+    (test_psrld, "psrld", Emit::emit_psrld),
+    (test_psrlq, "psrlq", Emit::emit_psrlq),
+}
+
+#[test]
+fn test_pmovmskb() {
+    test_reg_reg("pmovmskb", |v, (r1, r2)| Emit::emit_pmovmskb(v, r1, r2),
+                 REGS32, XMMS,
+                 |r1, r2| (Operand::Reg32(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+}
+
+#[test]
+fn test_pshufd() {
+    test_reg_reg("pshufd", |v, (r1, r2)| Emit::emit_pshufd(v, r1, r2, Operand::Imm8(0x1b)),
+                 XMMS, XMMS,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}, 0x1b", s1, s2));
+
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+    for &(r, s) in XMMS.iter() {
+        for &(m, ref ms) in mem_operands().iter() {
+            code.emit_pshufd(Operand::Xmm(r), xmmword_pointer(m), Operand::Imm8(0x1b)).unwrap();
+            expected_disasm.push(Some(format!("{}, xmmword ptr [{}], 0x1b", s, ms)));
+        }
+    }
+    test_disasm("pshufd", &expected_disasm, &code);
+}
+
+fn test_xmm_xmm_xmm(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>) {
+    test_reg_reg_reg(mnemonic, |v, (r1, r2, r3)| f(v, r1, r2, r3),
+                     XMMS, XMMS, XMMS,
+                     |r1, r2, r3| (Operand::Xmm(r1), Operand::Xmm(r2), Operand::Xmm(r3)),
+                     |s1, s2, s3| format!("{}, {}, {}", s1, s2, s3));
+}
+
+fn test_ymm_ymm_ymm(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>) {
+    test_reg_reg_reg(mnemonic, |v, (r1, r2, r3)| f(v, r1, r2, r3),
+                     YMMS, YMMS, YMMS,
+                     |r1, r2, r3| (Operand::Ymm(r1), Operand::Ymm(r2), Operand::Ymm(r3)),
+                     |s1, s2, s3| format!("{}, {}, {}", s1, s2, s3));
+}
+
+fn test_reg_reg_mem<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>,
+                       regs: &[(R, &'static str)], reg: fn(R) -> Operand,
+                       ptr: fn(Pointer) -> Operand, size: &str)
+    where R: Copy
+{
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for (i, &(r1, s1)) in regs.iter().enumerate() {
+        let (r2, s2) = regs[(i + 5) % regs.len()];
+        for &(m, ref ms) in mem_operands().iter() {
+            f(&mut code, reg(r1), reg(r2), ptr(m)).unwrap();
+            expected_disasm.push(Some(format!("{}, {}, {} ptr [{}]", s1, s2, size, ms)));
+        }
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+macro_rules! test_avx {
+    ($( ($test:ident, $mnemonic:expr, $f:path), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_xmm_xmm_xmm($mnemonic, $f);
+                test_reg_reg_mem($mnemonic, $f, &XMMS, Operand::Xmm, xmmword_pointer, "xmmword");
+                test_ymm_ymm_ymm($mnemonic, $f);
+                test_reg_reg_mem($mnemonic, $f, &YMMS, Operand::Ymm, ymmword_pointer, "ymmword");
+            }
+        )*
+    }
+}
+
+test_avx! {
+    (test_vaddps,      "vaddps",      Emit::emit_vaddps),
+    (test_vaddpd,      "vaddpd",      Emit::emit_vaddpd),
+    (test_vmulps,      "vmulps",      Emit::emit_vmulps),
+    (test_vmulpd,      "vmulpd",      Emit::emit_vmulpd),
+    (test_vfmadd132ps, "vfmadd132ps", Emit::emit_vfmadd132ps),
+    (test_vfmadd213ps, "vfmadd213ps", Emit::emit_vfmadd213ps),
+    (test_vfmadd231ps, "vfmadd231ps", Emit::emit_vfmadd231ps),
+    (test_vfmadd132pd, "vfmadd132pd", Emit::emit_vfmadd132pd),
+    (test_vfmadd213pd, "vfmadd213pd", Emit::emit_vfmadd213pd),
+    (test_vfmadd231pd, "vfmadd231pd", Emit::emit_vfmadd231pd),
+    (test_vpaddd,      "vpaddd",      Emit::emit_vpaddd),
+    (test_vpxor,       "vpxor",       Emit::emit_vpxor),
+    (test_vpcmpeqb,    "vpcmpeqb",    Emit::emit_vpcmpeqb),
+}
+
+macro_rules! test_avx_scalar {
+    ($( ($test:ident, $mnemonic:expr, $f:path, $ptr:expr, $size:expr), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_xmm_xmm_xmm($mnemonic, $f);
+                test_reg_reg_mem($mnemonic, $f, &XMMS, Operand::Xmm, $ptr, $size);
+            }
+        )*
+    }
+}
+
+test_avx_scalar! {
+    (test_vfmadd132ss, "vfmadd132ss", Emit::emit_vfmadd132ss, dword_pointer, "dword"),
+    (test_vfmadd213ss, "vfmadd213ss", Emit::emit_vfmadd213ss, dword_pointer, "dword"),
+    (test_vfmadd231ss, "vfmadd231ss", Emit::emit_vfmadd231ss, dword_pointer, "dword"),
+    (test_vfmadd132sd, "vfmadd132sd", Emit::emit_vfmadd132sd, qword_pointer, "qword"),
+    (test_vfmadd213sd, "vfmadd213sd", Emit::emit_vfmadd213sd, qword_pointer, "qword"),
+    (test_vfmadd231sd, "vfmadd231sd", Emit::emit_vfmadd231sd, qword_pointer, "qword"),
+}
+
+#[test]
+fn test_vpmovmskb() {
+    test_reg_reg("vpmovmskb", |v, (r1, r2)| Emit::emit_vpmovmskb(v, r1, r2),
+                 REGS32, XMMS,
+                 |r1, r2| (Operand::Reg32(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("vpmovmskb", |v, (r1, r2)| Emit::emit_vpmovmskb(v, r1, r2),
+                 REGS32, YMMS,
+                 |r1, r2| (Operand::Reg32(r1), Operand::Ymm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+}
+
+macro_rules! test_vpbroadcast {
+    ($( ($test:ident, $mnemonic:expr, $f:path, $ptr:expr, $size:expr), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_xmm_xmm($mnemonic, $f);
+                test_reg_mem($mnemonic, $f, &XMMS, Operand::Xmm, $ptr, $size);
+                test_reg_reg($mnemonic, |v, (r1, r2)| $f(v, r1, r2),
+                             YMMS, XMMS,
+                             |r1, r2| (Operand::Ymm(r1), Operand::Xmm(r2)),
+                             |s1, s2| format!("{}, {}", s1, s2));
+                test_reg_mem($mnemonic, $f, &YMMS, Operand::Ymm, $ptr, $size);
+            }
+        )*
+    }
+}
+
+test_vpbroadcast! {
+    (test_vpbroadcastb, "vpbroadcastb", Emit::emit_vpbroadcastb, byte_pointer, "byte"),
+    (test_vpbroadcastw, "vpbroadcastw", Emit::emit_vpbroadcastw, word_pointer, "word"),
+    (test_vpbroadcastd, "vpbroadcastd", Emit::emit_vpbroadcastd, dword_pointer, "dword"),
+    (test_vpbroadcastq, "vpbroadcastq", Emit::emit_vpbroadcastq, qword_pointer, "qword"),
+}
+
+#[test]
+fn test_vpermd() {
+    test_ymm_ymm_ymm("vpermd", Emit::emit_vpermd);
+    test_reg_reg_mem("vpermd", Emit::emit_vpermd, &YMMS, Operand::Ymm, ymmword_pointer, "ymmword");
+}
+
+#[test]
+fn test_vzeroupper() {
+    test_unit("vzeroupper", Emit::emit_vzeroupper);
+}
+
+fn test_evex_reg_reg_reg<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>,
+                            regs: [(R, &'static str); 8], reg: fn(R) -> Operand)
+    where R: Copy
+{
+    test_reg_reg_reg(mnemonic, |v, (r1, r2, r3)| f(v, r1, r2, r3),
+                     regs, regs, regs,
+                     |r1, r2, r3| (reg(r1), reg(r2), reg(r3)),
+                     |s1, s2, s3| format!("{}, {}, {}", s1, s2, s3));
+}
+
+fn test_evex_masked<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>,
+                       regs: [(R, &'static str); 8], reg: fn(R) -> Operand,
+                       masked: fn(Masked<R>) -> Operand)
+    where R: Copy
+{
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for (i, &(r, s)) in regs.iter().enumerate() {
+        let (k, ks) = [(K1, "k1"), (K2, "k2"), (K5, "k5"), (K7, "k7")][i % 4];
+        let (r2, s2) = regs[(i + 3) % regs.len()];
+        f(&mut code, masked(Masked::merge(r, k)), reg(r2), reg(r)).unwrap();
+        expected_disasm.push(Some(format!("{} {{{}}}, {}, {}", s, ks, s2, s)));
+        f(&mut code, masked(Masked::zero(r, k)), reg(r2), reg(r)).unwrap();
+        expected_disasm.push(Some(format!("{} {{{}}} {{z}}, {}, {}", s, ks, s2, s)));
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+fn test_evex_broadcast<R>(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand, Operand) -> Result<()>,
+                          regs: [(R, &'static str); 8], reg: fn(R) -> Operand,
+                          broadcast: fn(Pointer) -> Operand, size: &str, count: usize)
+    where R: Copy
+{
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+
+    for (i, &(r1, s1)) in regs.iter().enumerate() {
+        let (r2, s2) = regs[(i + 5) % regs.len()];
+        for &(m, ref ms) in mem_operands().iter() {
+            f(&mut code, reg(r1), reg(r2), broadcast(m)).unwrap();
+            expected_disasm.push(Some(format!("{}, {}, {} ptr [{}]{{1to{}}}", s1, s2, size, ms, count)));
+        }
+    }
+
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+macro_rules! test_avx512 {
+    ($( ($test:ident, $mnemonic:expr, $f:path, $broadcast:expr, $size:expr, $count:expr), )*) => {
+        $(
+            #[test]
+            fn $test() {
+                test_evex_reg_reg_reg($mnemonic, $f, EVEX_XMMS, Operand::Xmm);
+                test_evex_reg_reg_reg($mnemonic, $f, EVEX_YMMS, Operand::Ymm);
+                test_evex_reg_reg_reg($mnemonic, $f, EVEX_ZMMS, Operand::Zmm);
+                test_reg_reg_mem($mnemonic, $f, &EVEX_XMMS, Operand::Xmm, xmmword_pointer, "xmmword");
+                test_reg_reg_mem($mnemonic, $f, &EVEX_YMMS, Operand::Ymm, ymmword_pointer, "ymmword");
+                test_reg_reg_mem($mnemonic, $f, &EVEX_ZMMS, Operand::Zmm, zmmword_pointer, "zmmword");
+                test_evex_masked($mnemonic, $f, EVEX_XMMS, Operand::Xmm, Operand::MaskedXmm);
+                test_evex_masked($mnemonic, $f, EVEX_YMMS, Operand::Ymm, Operand::MaskedYmm);
+                test_evex_masked($mnemonic, $f, EVEX_ZMMS, Operand::Zmm, Operand::MaskedZmm);
+                test_evex_broadcast($mnemonic, $f, EVEX_XMMS, Operand::Xmm, $broadcast, $size, 16 / $count);
+                test_evex_broadcast($mnemonic, $f, EVEX_YMMS, Operand::Ymm, $broadcast, $size, 32 / $count);
+                test_evex_broadcast($mnemonic, $f, EVEX_ZMMS, Operand::Zmm, $broadcast, $size, 64 / $count);
+            }
+        )*
+    }
+}
+
+test_avx512! {
+    (test_evex_vaddps,      "vaddps",      Emit::emit_vaddps,      dword_broadcast, "dword", 4),
+    (test_evex_vaddpd,      "vaddpd",      Emit::emit_vaddpd,      qword_broadcast, "qword", 8),
+    (test_evex_vmulps,      "vmulps",      Emit::emit_vmulps,      dword_broadcast, "dword", 4),
+    (test_evex_vmulpd,      "vmulpd",      Emit::emit_vmulpd,      qword_broadcast, "qword", 8),
+    (test_evex_vfmadd132ps, "vfmadd132ps", Emit::emit_vfmadd132ps, dword_broadcast, "dword", 4),
+    (test_evex_vfmadd213ps, "vfmadd213ps", Emit::emit_vfmadd213ps, dword_broadcast, "dword", 4),
+    (test_evex_vfmadd231ps, "vfmadd231ps", Emit::emit_vfmadd231ps, dword_broadcast, "dword", 4),
+    (test_evex_vfmadd132pd, "vfmadd132pd", Emit::emit_vfmadd132pd, qword_broadcast, "qword", 8),
+    (test_evex_vfmadd213pd, "vfmadd213pd", Emit::emit_vfmadd213pd, qword_broadcast, "qword", 8),
+    (test_evex_vfmadd231pd, "vfmadd231pd", Emit::emit_vfmadd231pd, qword_broadcast, "qword", 8),
+    (test_evex_vpaddd,      "vpaddd",      Emit::emit_vpaddd,      dword_broadcast, "dword", 4),
+    (test_evex_vpaddq,      "vpaddq",      Emit::emit_vpaddq,      qword_broadcast, "qword", 8),
+    (test_vpxord,           "vpxord",      Emit::emit_vpxord,      dword_broadcast, "dword", 4),
+    (test_vpxorq,           "vpxorq",      Emit::emit_vpxorq,      qword_broadcast, "qword", 8),
+    (test_vpandd,           "vpandd",      Emit::emit_vpandd,      dword_broadcast, "dword", 4),
+    (test_vpandq,           "vpandq",      Emit::emit_vpandq,      qword_broadcast, "qword", 8),
+}
+
+#[test]
+fn test_vpaddq() {
+    test_xmm_xmm_xmm("vpaddq", Emit::emit_vpaddq);
+    test_reg_reg_mem("vpaddq", Emit::emit_vpaddq, &XMMS, Operand::Xmm, xmmword_pointer, "xmmword");
+    test_ymm_ymm_ymm("vpaddq", Emit::emit_vpaddq);
+    test_reg_reg_mem("vpaddq", Emit::emit_vpaddq, &YMMS, Operand::Ymm, ymmword_pointer, "ymmword");
+}
+
+#[test]
+fn test_evex_only_register() {
+    let mut code = Vec::new();
+    match code.emit_vpxor(Operand::Xmm(Xmm16), Operand::Xmm(Xmm1), Operand::Xmm(Xmm2)) {
+        Err(Error::EvexOnlyRegister(VectorReg::Xmm(Xmm16))) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match code.emit_vpxor(Operand::Xmm(Xmm1), Operand::Xmm(Xmm17), Operand::Xmm(Xmm2)) {
+        Err(Error::EvexOnlyRegister(VectorReg::Xmm(Xmm17))) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match code.emit_addps(Operand::Xmm(Xmm1), Operand::Xmm(Xmm31)) {
+        Err(Error::EvexOnlyRegister(VectorReg::Xmm(Xmm31))) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert!(code.is_empty());
+}
+
+#[test]
+fn test_evex_disp8() {
+    let mut code = Vec::new();
+    code.emit_vaddps(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), zmmword_pointer(Rax + 0x40)).unwrap();
+    code.emit_vaddps(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), zmmword_pointer(Rax - 0x2000)).unwrap();
+    code.emit_vaddps(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), zmmword_pointer(Rax + 0x2040)).unwrap();
+    code.emit_vaddps(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), zmmword_pointer(Rax + 0x42i8)).unwrap();
+    code.emit_vaddps(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), dword_broadcast(Rbx + Rcx*8 + 0x8i8)).unwrap();
+    code.emit_vaddpd(Operand::Zmm(Zmm1), Operand::Zmm(Zmm2), zmmword_pointer(Rax + 0x80)).unwrap();
+    assert_eq!(code, [
+        0x62, 0xf1, 0x6c, 0x48, 0x58, 0x48, 0x01,
+        0x62, 0xf1, 0x6c, 0x48, 0x58, 0x48, 0x80,
+        0x62, 0xf1, 0x6c, 0x48, 0x58, 0x88, 0x40, 0x20, 0x00, 0x00,
+        0x62, 0xf1, 0x6c, 0x48, 0x58, 0x88, 0x42, 0x00, 0x00, 0x00,
+        0x62, 0xf1, 0x6c, 0x58, 0x58, 0x4c, 0xcb, 0x02,
+        0x62, 0xf1, 0xed, 0x48, 0x58, 0x48, 0x02,
+    ]);
+}
+
+#[test]
+fn test_vmovdqu32() {
+    test_evex_move("vmovdqu32", Emit::emit_vmovdqu32);
+}
+
+#[test]
+fn test_vmovdqu64() {
+    test_evex_move("vmovdqu64", Emit::emit_vmovdqu64);
+}
+
+fn test_evex_move(mnemonic: &str, f: fn(&mut Vec<u8>, Operand, Operand) -> Result<()>) {
+    test_reg_reg(mnemonic, |v, (r1, r2)| f(v, r1, r2),
+                 EVEX_ZMMS, EVEX_ZMMS,
+                 |r1, r2| (Operand::Zmm(r1), Operand::Zmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg(mnemonic, |v, (r1, r2)| f(v, r1, r2),
+                 EVEX_XMMS, EVEX_XMMS,
+                 |r1, r2| (Operand::Xmm(r1), Operand::Xmm(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_mem(mnemonic, f, &EVEX_YMMS, Operand::Ymm, ymmword_pointer, "ymmword");
+    test_reg_mem(mnemonic, f, &EVEX_ZMMS, Operand::Zmm, zmmword_pointer, "zmmword");
+
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+    for &(m, ref ms) in mem_operands().iter() {
+        for &(r, s) in EVEX_ZMMS.iter() {
+            f(&mut code, zmmword_pointer(m), Operand::Zmm(r)).unwrap();
+            expected_disasm.push(Some(format!("zmmword ptr [{}], {}", ms, s)));
+        }
+    }
+    for &(r, s) in EVEX_ZMMS.iter() {
+        f(&mut code, Operand::MaskedZmm(Masked::zero(r, K3)), zmmword_pointer(Rdi + 0x40)).unwrap();
+        expected_disasm.push(Some(format!("{} {{k3}} {{z}}, zmmword ptr [rdi + 0x40]", s)));
+    }
+    test_disasm(mnemonic, &expected_disasm, &code);
+}
+
+#[test]
+fn test_kmovw() {
+    const KS: [(K, &'static str); 8] = [
+        (K0, "k0"), (K1, "k1"), (K2, "k2"), (K3, "k3"),
+        (K4, "k4"), (K5, "k5"), (K6, "k6"), (K7, "k7"),
+    ];
+    test_reg_reg("kmovw", |v, (r1, r2)| Emit::emit_kmovw(v, r1, r2),
+                 KS, KS,
+                 |r1, r2| (Operand::K(r1), Operand::K(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("kmovw", |v, (r1, r2)| Emit::emit_kmovw(v, r1, r2),
+                 KS, REGS32,
+                 |r1, r2| (Operand::K(r1), Operand::Reg32(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+    test_reg_reg("kmovw", |v, (r1, r2)| Emit::emit_kmovw(v, r1, r2),
+                 REGS32, KS,
+                 |r1, r2| (Operand::Reg32(r1), Operand::K(r2)),
+                 |s1, s2| format!("{}, {}", s1, s2));
+}
+
+const VSIB_BASES: [(Reg64, &'static str); 5] = [
+    (Rax, "rax"), (Rsp, "rsp"), (Rbp, "rbp"), (R12, "r12"), (R13, "r13"),
+];
+
+macro_rules! test_vgather {
+    ($name:ident, $mnemonic:expr, $f:ident, $ptr:ident) => {
+        #[test]
+        fn $name() {
+            let mut code = Vec::new();
+            let mut expected_disasm = Vec::new();
+            for &(base, b) in VSIB_BASES.iter() {
+                code.$f(Xmm1, $ptr(base + Xmm2 * 4), Xmm3).unwrap();
+                expected_disasm.push(Some(format!("xmm1, xmmword ptr [{} + xmm2*4], xmm3", b)));
+                code.$f(Xmm9, $ptr(base + Xmm10 * 8 + 0x10i8), Xmm15).unwrap();
+                expected_disasm.push(Some(format!("xmm9, xmmword ptr [{} + xmm10*8 + 0x10], xmm15", b)));
+                code.$f(Ymm4, $ptr(base + Ymm5 * 1 - 0x1000), Ymm6).unwrap();
+                expected_disasm.push(Some(format!("ymm4, ymmword ptr [{} + ymm5 - 0x1000], ymm6", b)));
+                code.$f(Ymm12, $ptr(base + Ymm13 * 2), Ymm14).unwrap();
+                expected_disasm.push(Some(format!("ymm12, ymmword ptr [{} + ymm13*2], ymm14", b)));
+            }
+            test_disasm($mnemonic, &expected_disasm, &code);
+
+            match code.$f(Xmm1, $ptr(Rax + Xmm1 * 4), Xmm3) {
+                Err(Error::InvalidOperands) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+            match code.$f(Ymm1, $ptr(Rax + Ymm2 * 4), Ymm2) {
+                Err(Error::InvalidOperands) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+            match code.$f(Xmm5, $ptr(Rax + Xmm2 * 4), Xmm5) {
+                Err(Error::InvalidOperands) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+            match code.$f(Xmm1, $ptr(Rax + Xmm16 * 4), Xmm3) {
+                Err(Error::EvexOnlyRegister(VectorReg::Xmm(Xmm16))) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+}
+
+test_vgather!(test_vpgatherdd, "vpgatherdd", emit_vpgatherdd, dword_ptr);
+test_vgather!(test_vpgatherqq, "vpgatherqq", emit_vpgatherqq, qword_ptr);
+test_vgather!(test_vgatherdps, "vgatherdps", emit_vgatherdps, dword_ptr);
+
+macro_rules! test_vscatter {
+    ($name:ident, $mnemonic:expr, $f:ident, $ptr:ident, $n:expr) => {
+        #[test]
+        fn $name() {
+            let mut code = Vec::new();
+            let mut expected_disasm = Vec::new();
+            for &(base, b) in VSIB_BASES.iter() {
+                for &(index, i) in EVEX_ZMMS.iter() {
+                    code.$f(Masked::merge($ptr(base + index * 4), K1), Zmm1).unwrap();
+                    expected_disasm.push(Some(format!("zmmword ptr [{} + {}*4] {{k1}}, zmm1", b, i)));
+                }
+                code.$f(Masked::merge($ptr(base + Zmm31 * 8 + $n), K7), Zmm8).unwrap();
+                expected_disasm.push(Some(format!("zmmword ptr [{} + zmm31*8 + {:#x}] {{k7}}, zmm8", b, $n)));
+                code.$f(Masked::merge($ptr(base + Zmm2 * 2 + 0x1001), K2), Zmm24).unwrap();
+                expected_disasm.push(Some(format!("zmmword ptr [{} + zmm2*2 + 0x1001] {{k2}}, zmm24", b)));
+            }
+            test_disasm($mnemonic, &expected_disasm, &code);
+
+            match code.$f(Masked::merge($ptr(Rax + Zmm2 * 4), K0), Zmm1) {
+                Err(Error::InvalidOperands) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+            match code.$f(Masked::merge($ptr(Rax + Zmm1 * 4), K1), Zmm1) {
+                Err(Error::InvalidOperands) => {}
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+}
+
+test_vscatter!(test_vpscatterdd, "vpscatterdd", emit_vpscatterdd, dword_ptr, 0x40);
+test_vscatter!(test_vpscatterqq, "vpscatterqq", emit_vpscatterqq, qword_ptr, 0x80);
+
+#[test]
+fn test_vsib_disp8() {
+    let mut code = Vec::new();
+    code.emit_vpscatterdd(Masked::merge(dword_ptr(Rax + Zmm2 * 4 + 0x40), K1), Zmm1).unwrap();
+    code.emit_vpscatterqq(Masked::merge(qword_ptr(R13 + Zmm18 * 8 - 0x10i8), K1), Zmm17).unwrap();
+    code.emit_vpscatterdd(Masked::merge(dword_ptr(Rax + Zmm2 * 4 + 0x2i8), K1), Zmm1).unwrap();
+    assert_eq!(code, [
+        0x62, 0xf2, 0x7d, 0x49, 0xa0, 0x4c, 0x90, 0x10,
+        0x62, 0xc2, 0xfd, 0x41, 0xa1, 0x4c, 0xd5, 0xfe,
+        0x62, 0xf2, 0x7d, 0x49, 0xa0, 0x8c, 0x90, 0x02, 0x00, 0x00, 0x00,
+    ]);
+}
+
+#[test]
+fn test_lock_add() {
+    test_lock_op2!("lock add", Emit::emit_add);
+}
+
+#[test]
+fn test_lock_or() {
+    test_lock_op2!("lock or", Emit::emit_or);
+}
+
+#[test]
+fn test_lock_adc() {
+    test_lock_op2!("lock adc", Emit::emit_adc);
+}
+
+#[test]
+fn test_lock_sbb() {
+    test_lock_op2!("lock sbb", Emit::emit_sbb);
+}
+
+#[test]
+fn test_lock_and() {
+    test_lock_op2!("lock and", Emit::emit_and);
+}
+
+#[test]
+fn test_lock_sub() {
+    test_lock_op2!("lock sub", Emit::emit_sub);
+}
+
+#[test]
+fn test_lock_xor() {
+    test_lock_op2!("lock xor", Emit::emit_xor);
+}
+
+#[test]
+fn test_lock_not() {
+    test_lock_op1!("lock not", Emit::emit_not);
+}
+
+#[test]
+fn test_lock_neg() {
+    test_lock_op1!("lock neg", Emit::emit_neg);
+}
+
+#[test]
+fn test_lock_inc() {
+    test_lock_op1!("lock inc", Emit::emit_inc);
+}
+
+#[test]
+fn test_lock_dec() {
+    test_lock_op1!("lock dec", Emit::emit_dec);
+}
+
+#[test]
+fn test_lock_register_destination() {
+    let mut code = Vec::new();
+    match code.emit_add(Lock(Operand::Reg32(Eax)), Operand::Reg32(Ecx)) {
+        Err(Error::InvalidOperands) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match code.emit_inc(Lock(Operand::Reg64(Rax))) {
+        Err(Error::InvalidOperands) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match code.emit_xadd(Lock(Operand::Reg8(Al)), Operand::Reg8(Cl)) {
+        Err(Error::InvalidOperands) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match code.emit_add(Lock(dword_ptr(Rax + Rsp * 2)), Ecx) {
+        Err(Error::InvalidIndexRegister(Rsp)) => {}
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert!(code.is_empty());
+}
+
+#[test]
+fn test_lock_sext_imm8() {
+    let mut code = Vec::new();
+    code.emit_add(Lock(dword_pointer(Rax)), Operand::Imm8(0xff)).unwrap();
+    code.emit_sub(Lock(qword_pointer(Rcx)), Operand::Imm8(0x01)).unwrap();
+    assert_eq!(code, [0xf0, 0x83, 0x00, 0xff, 0xf0, 0x48, 0x83, 0x29, 0x01]);
+}
+
+macro_rules! test_exchange_op {
+    ($name:ident, $mnemonic:expr, $lock_mnemonic:expr, $f:path) => {
+        #[test]
+        fn $name() {
+            test_reg8_reg8($mnemonic, $f);
+            test_reg16_reg16($mnemonic, $f);
+            test_reg32_reg32($mnemonic, $f);
+            test_reg64_reg64($mnemonic, $f);
+
+            test_byte_ptr_reg8($mnemonic, $f);
+            test_word_ptr_reg16($mnemonic, $f);
+            test_dword_ptr_reg32($mnemonic, $f);
+            test_qword_ptr_reg64($mnemonic, $f);
+
+            test_byte_ptr_reg8($lock_mnemonic, |v, d, s| $f(v, Lock(d), s));
+            test_word_ptr_reg16($lock_mnemonic, |v, d, s| $f(v, Lock(d), s));
+            test_dword_ptr_reg32($lock_mnemonic, |v, d, s| $f(v, Lock(d), s));
+            test_qword_ptr_reg64($lock_mnemonic, |v, d, s| $f(v, Lock(d), s));
+        }
+    }
+}
+
+test_exchange_op!(test_xadd, "xadd", "lock xadd", Emit::emit_xadd);
+test_exchange_op!(test_cmpxchg, "cmpxchg", "lock cmpxchg", Emit::emit_cmpxchg);
+
+#[test]
+fn test_cmpxchg8b() {
+    test_qword_ptr("cmpxchg8b", Emit::emit_cmpxchg8b);
+    test_qword_ptr("lock cmpxchg8b", |v, a| Emit::emit_cmpxchg8b(v, Lock(a)));
+}
+
+#[test]
+fn test_cmpxchg16b() {
+    let mut code = Vec::new();
+    let mut expected_disasm = Vec::new();
+    for &(base, b) in REGS64.iter() {
+        if base == Rsp {
+            continue;
+        }
+        code.emit_cmpxchg16b(xmmword_pointer(base)).unwrap();
+        expected_disasm.push(Some(format!("xmmword ptr [{}]", b)));
+        code.emit_cmpxchg16b(xmmword_pointer(base + R9 * 8 + 0x10)).unwrap();
+        expected_disasm.push(Some(format!("xmmword ptr [{} + r9*8 + 0x10]", b)));
+    }
+    test_disasm("cmpxchg16b", &expected_disasm, &code);
+
+    let mut code = Vec::new();
+    code.emit_cmpxchg16b(Lock(xmmword_pointer(Rdi))).unwrap();
+    test_disasm("lock cmpxchg16b", &[Some("xmmword ptr [rdi]")], &code);
+}
+
+#[test]
+fn test_movs() {
+    test_string("movsb", "byte ptr [rdi], byte ptr [rsi]", |v| v.emit_movs(string::B));
+    test_string("movsw", "word ptr [rdi], word ptr [rsi]", |v| v.emit_movs(string::W));
+    test_string("movsd", "dword ptr [rdi], dword ptr [rsi]", |v| v.emit_movs(string::D));
+    test_string("movsq", "qword ptr [rdi], qword ptr [rsi]", |v| v.emit_movs(string::Q));
+    test_string("rep movsb", "byte ptr [rdi], byte ptr [rsi]", |v| v.emit_movs(Rep(string::B)));
+    test_string("rep movsq", "qword ptr [rdi], qword ptr [rsi]", |v| v.emit_movs(Rep(string::Q)));
+}
+
+#[test]
+fn test_stos() {
+    test_string("stosb", "byte ptr [rdi], al", |v| v.emit_stos(string::B));
+    test_string("stosw", "word ptr [rdi], ax", |v| v.emit_stos(string::W));
+    test_string("stosd", "dword ptr [rdi], eax", |v| v.emit_stos(string::D));
+    test_string("stosq", "qword ptr [rdi], rax", |v| v.emit_stos(string::Q));
+    test_string("rep stosb", "byte ptr [rdi], al", |v| v.emit_stos(Rep(string::B)));
+    test_string("rep stosd", "dword ptr [rdi], eax", |v| v.emit_stos(Rep(string::D)));
+}
+
+#[test]
+fn test_lods() {
+    test_string("lodsb", "al, byte ptr [rsi]", |v| v.emit_lods(string::B));
+    test_string("lodsw", "ax, word ptr [rsi]", |v| v.emit_lods(string::W));
+    test_string("lodsd", "eax, dword ptr [rsi]", |v| v.emit_lods(string::D));
+    test_string("lodsq", "rax, qword ptr [rsi]", |v| v.emit_lods(string::Q));
+    test_string("rep lodsq", "rax, qword ptr [rsi]", |v| v.emit_lods(Rep(string::Q)));
+}
+
+#[test]
+fn test_scas() {
+    test_string("scasb", "al, byte ptr [rdi]", |v| v.emit_scas(string::B));
+    test_string("scasw", "ax, word ptr [rdi]", |v| v.emit_scas(string::W));
+    test_string("scasd", "eax, dword ptr [rdi]", |v| v.emit_scas(string::D));
+    test_string("scasq", "rax, qword ptr [rdi]", |v| v.emit_scas(string::Q));
+    test_string("repe scasb", "al, byte ptr [rdi]", |v| v.emit_scas(Repe(string::B)));
+    test_string("repne scasb", "al, byte ptr [rdi]", |v| v.emit_scas(Repne(string::B)));
+}
+
+#[test]
+fn test_cmps() {
+    test_string("cmpsb", "byte ptr [rsi], byte ptr [rdi]", |v| v.emit_cmps(string::B));
+    test_string("cmpsw", "word ptr [rsi], word ptr [rdi]", |v| v.emit_cmps(string::W));
+    test_string("cmpsd", "dword ptr [rsi], dword ptr [rdi]", |v| v.emit_cmps(string::D));
+    test_string("cmpsq", "qword ptr [rsi], qword ptr [rdi]", |v| v.emit_cmps(string::Q));
+    test_string("repe cmpsb", "byte ptr [rsi], byte ptr [rdi]", |v| v.emit_cmps(Repe(string::B)));
+    test_string("repne cmpsq", "qword ptr [rsi], qword ptr [rdi]", |v| v.emit_cmps(Repne(string::Q)));
+}
+
+#[test]
+fn test_nop() {
+    let capstone = Capstone::new().x86().mode(ArchMode::Mode64).build().unwrap();
+    for len in 1..12 {
+        let mut code = Vec::new();
+        code.emit_nop(len).unwrap();
+        assert_eq!(code.len(), len as usize);
+        let disasm = capstone.disasm_all(&code, 0x0).unwrap();
+        assert_eq!(disasm.len(), 1, "{:?}", code);
+        assert_eq!(disasm.iter().next().unwrap().mnemonic(), Some("nop"));
+    }
+}
+
+#[test]
+fn test_nop_invalid_len() {
+    let mut code = Vec::new();
+    assert!(matches!(code.emit_nop(0), Err(Error::InvalidOperands)));
+    assert!(matches!(code.emit_nop(12), Err(Error::InvalidOperands)));
+    assert!(code.is_empty());
+}
+
+#[test]
+fn test_align() {
+    let capstone = Capstone::new().x86().mode(ArchMode::Mode64).build().unwrap();
+    for start in 0..40 {
+        let mut code = vec![0x90; start];
+        code.emit_align(32).unwrap();
+        assert_eq!(code.len() % 32, 0);
+        assert!(code.len() - start < 32);
+        let padding = &code[start..];
+        let disasm = capstone.disasm_all(padding, 0x0).unwrap();
+        assert_eq!(disasm.len(), padding.len().div_ceil(11));
+        assert!(disasm.iter().all(|i| i.mnemonic() == Some("nop")));
+    }
+
+    let mut code = vec![0x90; 5];
+    code.emit_align(1).unwrap();
+    assert_eq!(code.len(), 5);
+    assert!(matches!(code.emit_align(0), Err(Error::InvalidOperands)));
+}
+
+#[test]
+fn test_segment() {
+    let mut code = Vec::new();
+    code.emit_mov(Rax, QWord(seg(Fs, 0x28i32))).unwrap();
+    code.emit_mov(Word(seg(Gs, Rax)), Cx).unwrap();
+    code.emit_add(QWord(seg(Fs, R8 + R9 * 8 + 0x10i8)), R10).unwrap();
+    code.emit_mov(Byte(seg(Es, Rdi)), 1u8).unwrap();
+    code.emit_inc(DWord(Seg(Ss, Pointer::Base(Rsp)))).unwrap();
+    code.emit_xadd(Lock(QWord(seg(Gs, Rbx))), Rcx).unwrap();
+    assert_eq!(disasm_at(&code, 0), [
+        "mov rax, qword ptr fs:[0x28]",
+        "mov word ptr gs:[rax], cx",
+        "add qword ptr fs:[r8 + r9*8 + 0x10], r10",
+        "mov byte ptr es:[rdi], 1",
+        "inc dword ptr ss:[rsp]",
+        "lock xadd qword ptr gs:[rbx], rcx",
+    ]);
+
+    // the segment override comes before the operand size prefix and REX
+    let mut code = Vec::new();
+    code.emit_mov(Word(seg(Gs, R8)), Cx).unwrap();
+    assert_eq!(code, [0x65, 0x66, 0x41, 0x89, 0x08]);
+
+    let mut code = Vec::new();
+    Segmented(Fs, &mut code).emit_mov(Operand::Reg64(Rax), qword_pointer(0x28)).unwrap();
+    Segmented(Gs, &mut code).emit_add(word_pointer(R8), Operand::Reg16(Cx)).unwrap();
+    Segmented(Gs, &mut code).emit_xadd(Lock(qword_pointer(Rbx)), Operand::Reg64(Rcx)).unwrap();
+    assert_eq!(disasm_at(&code, 0), [
+        "mov rax, qword ptr fs:[0x28]",
+        "add word ptr gs:[r8], cx",
+        "lock xadd qword ptr gs:[rbx], rcx",
+    ]);
+}